@@ -0,0 +1,30 @@
+use std::env;
+
+use afl::{
+    benchmark::compare_with_squiggle,
+    presentation::Presenter,
+    run_model,
+    tipping::{
+        models::glicko::{GlickoModel, GlickoModelInitParams},
+        source::{MatchSource, SquiggleApi},
+    },
+    RunOptions,
+};
+
+#[tokio::main]
+async fn main() -> afl::Result<()> {
+    let year = 2024;
+    let email = env::var("AFL_USER_EMAIL").expect("AFL_USER_EMAIL environment variable not set.");
+    let source = SquiggleApi::new(email, None);
+
+    let model = GlickoModel::new(GlickoModelInitParams::default())?;
+    let (_, _, _, _, _, evaluations) =
+        run_model(year, &source, model, RunOptions::default()).await?;
+
+    let matches = source.get_season(year).await?;
+    let tips = source.get_tips(year).await?;
+    let comparisons = compare_with_squiggle(&evaluations, &tips, &matches)?;
+
+    Presenter::console().display_benchmark(&comparisons);
+    Ok(())
+}
@@ -1,26 +1,36 @@
 use std::{collections::HashMap, env};
 
-use afl::{optimise, run_model, presentation::Presenter, tipping::squiggle::{get_squiggle_season, get_squiggle_teams}};
+use afl::{optimise, run_model, RunOptions, presentation::{CsvFormatter, Presenter}, tipping::{models::glicko::{GlickoModel, GlickoModelInitParams}, source::{MatchSource, SquiggleApi}}};
 use futures::executor::block_on;
 
+fn build_model(offsets: HashMap<String, f64>) -> afl::Result<GlickoModel> {
+    GlickoModel::new(GlickoModelInitParams {
+        offsets: Some(offsets),
+        ..Default::default()
+    })
+}
+
 #[tokio::main]
-async fn main() {
+async fn main() -> afl::Result<()> {
     let year = 2024;
     let email = env::var("AFL_USER_EMAIL").expect("AFL_USER_EMAIL environment variable not set.");
-    let cache = "optimise_cache".to_string();
+    let source = SquiggleApi::new(email, Some("optimise_cache".to_string()));
 
-    let matches = block_on(get_squiggle_season(year, email.clone(), cache));
-    
-    let teams: Vec<String> = Vec::from_iter(get_squiggle_teams(&matches));
+    let teams: Vec<String> = block_on(source.get_teams(year))?
+        .into_iter()
+        .map(|team| team.name)
+        .collect();
 
-    let offsets: HashMap<String, f64> = optimise(year, teams, email.clone());
+    let offsets: HashMap<String, f64> = optimise(year, teams, source.clone(), build_model)?;
     
     let presenter = Presenter::console();
     presenter.display_offsets(&offsets);
 
-    let (model, margin_model, perf, tips) = block_on(run_model(year, None, Some(offsets), email));
+    let (model, margin_model, _, perf, tips, evaluations) = block_on(run_model(year, &source, build_model(offsets)?, RunOptions::default()))?;
 
     presenter.display_model_summary(&model);
     presenter.display_tips(&tips);
     presenter.display_performance_summary(year, &perf, &margin_model);
+    Presenter::new(Box::new(CsvFormatter::new())).display_evaluations(&evaluations);
+    Ok(())
 }
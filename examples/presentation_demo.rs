@@ -1,20 +1,14 @@
-use std::{collections::HashMap, env};
+use std::collections::HashMap;
 use afl::{
-    run_model, 
     presentation::{Presenter, JsonFormatter, ConsoleFormatter},
     tipping::models::glicko::{GlickoModel, GlickoModelInitParams},
 };
-use chrono::Datelike;
 
 #[tokio::main]
 async fn main() {
     // This example demonstrates different presentation formats
     println!("=== AFL Prediction Presentation Demo ===\n");
     
-    let email = env::var("AFL_USER_EMAIL").unwrap_or_else(|_| "demo@example.com".to_string());
-    let current_date = chrono::Utc::now();
-    let year = current_date.year();
-    
     // Sample team offsets for demo
     let mut offsets: HashMap<String, f64> = HashMap::new();
     offsets.insert("Richmond".to_string(), 0.001_694);
@@ -45,9 +39,13 @@ async fn main() {
         scale_factor: None,
         starting_elo: None,
         volatility_constraint: None,
+        rating_period_days: None,
+        home_advantage: None,
+        venue_offsets: None,
+        outcome: None,
     };
     
-    let demo_model = GlickoModel::new(params);
+    let demo_model = GlickoModel::new(params).unwrap();
     simple_presenter.display_model_summary(&demo_model);
     
     println!("3. JSON Format:");
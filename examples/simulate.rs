@@ -0,0 +1,36 @@
+use std::env;
+
+use afl::{
+    presentation::Presenter,
+    run_model,
+    simulation::{simulate_remaining_season, SimulationOptions},
+    tipping::{
+        models::glicko::{GlickoModel, GlickoModelInitParams},
+        source::SquiggleApi,
+    },
+    RunOptions,
+};
+use chrono::Datelike;
+
+#[tokio::main]
+async fn main() -> afl::Result<()> {
+    let year = chrono::Utc::now().year();
+    let email = env::var("AFL_USER_EMAIL").expect("AFL_USER_EMAIL environment variable not set.");
+    let source = SquiggleApi::new(email, None);
+
+    let model = GlickoModel::new(GlickoModelInitParams::default())?;
+    let (model, margin_model, calibration, _, _, _) =
+        run_model(year, &source, model, RunOptions::default()).await?;
+    let simulation = simulate_remaining_season(
+        year,
+        &source,
+        &model,
+        &margin_model,
+        &calibration,
+        &SimulationOptions::default(),
+    )
+    .await?;
+
+    Presenter::console().display_season_simulation(&simulation);
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::Result,
+    tipping::{
+        models::{calibration::Calibration, margin::MarginModel, RatingModel},
+        source::MatchSource,
+        teams::TeamId,
+        MatchEvaluation, SquiggleMatch,
+    },
+    RunOptions,
+};
+
+/// Tipping record over a set of evaluated matches
+#[derive(Debug, Clone, Default)]
+pub struct BacktestSummary {
+    pub games: u32,
+    pub correct: u32,
+    pub bits: f64,
+    pub total_abs_error: u64,
+}
+
+impl BacktestSummary {
+    pub fn add(&mut self, evaluation: &MatchEvaluation) {
+        self.games += 1;
+        if evaluation.correct {
+            self.correct += 1;
+        }
+        self.bits += evaluation.bits;
+        self.total_abs_error += evaluation.abs_error as u64;
+    }
+
+    /// Fraction of games tipped correctly, or 0 with no games
+    pub fn accuracy(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.games as f64
+    }
+
+    /// Mean absolute margin error in points, or 0 with no games
+    pub fn mean_abs_error(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.total_abs_error as f64 / self.games as f64
+    }
+}
+
+pub struct Backtest<M: RatingModel> {
+    pub model: M,
+    pub margin_model: MarginModel,
+    /// Every completed match in the backtest, in the order it was played
+    pub evaluations: Vec<MatchEvaluation>,
+}
+
+impl<M: RatingModel> Backtest<M> {
+    pub fn overall(&self) -> BacktestSummary {
+        let mut summary = BacktestSummary::default();
+        for evaluation in &self.evaluations {
+            summary.add(evaluation);
+        }
+        summary
+    }
+
+    pub fn by_season(&self) -> BTreeMap<i32, BacktestSummary> {
+        let mut seasons: BTreeMap<i32, BacktestSummary> = BTreeMap::new();
+        for evaluation in &self.evaluations {
+            seasons.entry(evaluation.year).or_default().add(evaluation);
+        }
+        seasons
+    }
+
+    /// Summaries keyed by year and round
+    pub fn by_round(&self) -> BTreeMap<(i32, i32), BacktestSummary> {
+        let mut rounds: BTreeMap<(i32, i32), BacktestSummary> = BTreeMap::new();
+        for evaluation in &self.evaluations {
+            rounds
+                .entry((evaluation.year, evaluation.round))
+                .or_default()
+                .add(evaluation);
+        }
+        rounds
+    }
+}
+
+/// Games with both teams known, in the order they were played
+pub(crate) fn chronological(season: Vec<SquiggleMatch>) -> Result<Vec<SquiggleMatch>> {
+    let mut games = vec![];
+    for game in season {
+        if game.hteam.is_some() && game.ateam.is_some() {
+            games.push((game.get_match()?.date, game));
+        }
+    }
+    games.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.id.cmp(&b.1.id)));
+    Ok(games.into_iter().map(|(_, game)| game).collect())
+}
+
+/// Replay every season from `start_year` to `end_year` inclusive in the order the games
+/// were played, predicting each completed match before the model sees its result. The
+/// model is first trained on the warm-up seasons before `start_year`. Probabilities are
+/// calibrated against the model's predictions in earlier seasons, and the margin model is
+/// refitted at the end of each round.
+pub async fn backtest<S: MatchSource, M: RatingModel>(
+    start_year: i32,
+    end_year: i32,
+    source: &S,
+    mut model: M,
+    options: RunOptions,
+) -> Result<Backtest<M>> {
+    let warmup_seasons = options.warmup_seasons.unwrap_or(1) as i32;
+    let mut seasons = vec![];
+    for year in (start_year - warmup_seasons)..=end_year {
+        for team in source.get_teams(year).await? {
+            model.add_team(TeamId::from_squiggle(Some(team.id)), &team.name);
+        }
+        seasons.push((year, chronological(source.get_season(year).await?)?));
+    }
+
+    let mut margin_model = MarginModel::new(None);
+    let mut evaluations = vec![];
+    let mut calibration_samples = vec![];
+    for (year, season) in seasons {
+        let calibration = Calibration::fit(&calibration_samples);
+        let mut current_round = None;
+        for game in season {
+            if game.timestr != Some("Full Time".to_string()) {
+                continue;
+            }
+            let match_ = game.get_match()?;
+            let result = game.get_match_result()?;
+            let mut prediction = model.predict(&match_)?;
+            calibration_samples.push((prediction.prediction, result.home_outcome()));
+            if year < start_year {
+                model.update(&match_, &result)?;
+                continue;
+            }
+
+            if current_round.is_some_and(|round| round != game.round)
+                && margin_model.data.probs.len() > 25
+            {
+                margin_model.update()?;
+            }
+            current_round = Some(game.round);
+
+            prediction.prediction = calibration.calibrate(prediction.prediction);
+            prediction.home_team_win = prediction.prediction >= 0.5;
+            prediction.pred_margin =
+                margin_model.predict(prediction.prediction.max(1.0 - prediction.prediction));
+            let evaluation = MatchEvaluation::new(&game, &match_, &prediction, &result);
+            model.update(&match_, &result)?;
+            margin_model.add_result(
+                evaluation.probability,
+                result.winning_margin.unwrap_or(0),
+                evaluation.correct,
+            );
+            evaluations.push(evaluation);
+        }
+        if year < end_year {
+            if let Some(regression) = &options.season_regression {
+                model.new_season(regression);
+            }
+        }
+    }
+
+    Ok(Backtest {
+        model,
+        margin_model,
+        evaluations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::{
+        game,
+        models::elo::{EloModel, EloModelInitParams},
+        source::VecSource,
+    };
+    use futures::executor::block_on;
+
+    const TOLERANCE: f64 = 0.000_001;
+
+    #[test]
+    fn test_backtest() {
+        let source = VecSource::new(vec![
+            game(1, 2022, 1, ("A", "B"), Some((100, 50))),
+            // Listed out of order, but played before game 2
+            game(3, 2023, 1, ("A", "B"), Some((80, 70))),
+            game(2, 2023, 2, ("A", "B"), Some((60, 90))),
+            game(4, 2024, 1, ("A", "B"), Some((100, 60))),
+            game(5, 2024, 2, ("A", "B"), None),
+        ]);
+        let model = EloModel::new(EloModelInitParams::default());
+
+        let result = block_on(backtest(2023, 2024, &source, model, RunOptions::default())).unwrap();
+
+        let ids: Vec<i32> = result.evaluations.iter().map(|x| x.game_id).collect();
+        assert_eq!(ids, vec![3, 2, 4]);
+        let first = &result.evaluations[0];
+        assert_eq!(first.tip, "A");
+        assert!(first.correct);
+        assert!((first.bits - (1.0 + first.probability.log2())).abs() < TOLERANCE);
+        assert!(!result.evaluations[1].correct);
+        assert_eq!(result.evaluations[1].actual_margin, -30);
+
+        let seasons = result.by_season();
+        assert_eq!(seasons[&2023].games, 2);
+        assert_eq!(seasons[&2023].correct, 1);
+        assert_eq!(seasons[&2024].games, 1);
+        assert_eq!(result.by_round().len(), 3);
+        assert_eq!(result.overall().games, 3);
+    }
+
+    #[test]
+    fn test_empty_summary() {
+        let summary = BacktestSummary::default();
+        assert_eq!(summary.accuracy(), 0.0);
+        assert_eq!(summary.mean_abs_error(), 0.0);
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    backtest::BacktestSummary,
+    error::Result,
+    tipping::{squiggle::SquiggleTip, MatchEvaluation, MatchPrediction, SquiggleMatch},
+};
+
+/// Our record against one Squiggle source, over the games both of us tipped
+#[derive(Debug, Clone)]
+pub struct SourceComparison {
+    pub source: String,
+    pub ours: BacktestSummary,
+    pub theirs: BacktestSummary,
+}
+
+/// Score a Squiggle source's tip the same way our own predictions are scored
+fn evaluate_tip(game: &SquiggleMatch, tip: &SquiggleTip) -> Result<Option<MatchEvaluation>> {
+    let Some(hconfidence) = tip.hconfidence else {
+        return Ok(None);
+    };
+    let match_ = game.get_match()?;
+    let result = game.get_match_result()?;
+    let prediction = hconfidence / 100.0;
+    // The named tip only settles an even game, so a tip that contradicts its own
+    // confidence is scored on the confidence
+    let home_team_win = match &tip.tip {
+        Some(team) if prediction == 0.5 => *team == match_.home_team,
+        _ => prediction >= 0.5,
+    };
+    let prediction = MatchPrediction {
+        prediction,
+        pred_margin: tip.margin.unwrap_or(0.0).abs().round() as u32,
+        home_team_win,
+        home_score: None,
+        away_score: None,
+        margin_distribution: None,
+    };
+    Ok(Some(MatchEvaluation::new(
+        game,
+        &match_,
+        &prediction,
+        &result,
+    )))
+}
+
+/// Compare our evaluated predictions with every source in `tips`, head to head on the
+/// completed games in `matches` that both tipped. Sources are ordered by bits, best first.
+pub fn compare_with_squiggle(
+    evaluations: &[MatchEvaluation],
+    tips: &[SquiggleTip],
+    matches: &[SquiggleMatch],
+) -> Result<Vec<SourceComparison>> {
+    let ours: HashMap<i32, &MatchEvaluation> = evaluations.iter().map(|x| (x.game_id, x)).collect();
+    let games: HashMap<i32, &SquiggleMatch> = matches
+        .iter()
+        .filter(|x| ours.contains_key(&x.id))
+        .map(|x| (x.id, x))
+        .collect();
+
+    let mut comparisons: BTreeMap<String, SourceComparison> = BTreeMap::new();
+    for tip in tips {
+        let (Some(game), Some(our_evaluation)) = (games.get(&tip.gameid), ours.get(&tip.gameid))
+        else {
+            continue;
+        };
+        let Some(their_evaluation) = evaluate_tip(game, tip)? else {
+            continue;
+        };
+        let comparison =
+            comparisons
+                .entry(tip.source.clone())
+                .or_insert_with(|| SourceComparison {
+                    source: tip.source.clone(),
+                    ours: BacktestSummary::default(),
+                    theirs: BacktestSummary::default(),
+                });
+        comparison.ours.add(our_evaluation);
+        comparison.theirs.add(&their_evaluation);
+    }
+
+    let mut comparisons: Vec<SourceComparison> = comparisons.into_values().collect();
+    comparisons.sort_by(|a, b| b.theirs.bits.total_cmp(&a.theirs.bits));
+    Ok(comparisons)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::{evaluation, game};
+
+    const TOLERANCE: f64 = 0.000_001;
+
+    fn tip(gameid: i32, source: &str, team: &str, hconfidence: f64, margin: f64) -> SquiggleTip {
+        SquiggleTip {
+            gameid,
+            sourceid: 0,
+            source: source.to_string(),
+            year: Some(2024),
+            round: Some(1),
+            hteam: Some("A".to_string()),
+            ateam: Some("B".to_string()),
+            tip: Some(team.to_string()),
+            hconfidence: Some(hconfidence),
+            margin: Some(margin),
+            bits: None,
+            err: None,
+            correct: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_with_squiggle() {
+        let played = |id, scores| game(id, 2024, 1, ("A", "B"), Some(scores));
+        let matches = vec![
+            played(1, (90, 70)),
+            played(2, (60, 80)),
+            played(3, (100, 50)),
+        ];
+        let ours = |game_id, correct, bits, abs_error| MatchEvaluation {
+            bits,
+            abs_error,
+            ..evaluation(game_id, 0.6, 0, correct)
+        };
+        let evaluations = vec![ours(1, true, 0.2, 10), ours(2, false, -0.3, 30)];
+        let tips = vec![
+            tip(1, "Good", "A", 75.0, 20.0),
+            tip(2, "Good", "B", 40.0, 10.0),
+            tip(1, "Bad", "B", 30.0, 12.0),
+            // Names B but gives A a 75% chance, so it is scored as a tip for A
+            tip(1, "Muddled", "B", 75.0, 20.0),
+            // We have no prediction for game 3, so it is left out
+            tip(3, "Bad", "A", 90.0, 40.0),
+        ];
+
+        let comparisons = compare_with_squiggle(&evaluations, &tips, &matches).unwrap();
+
+        assert_eq!(comparisons.len(), 3);
+        let good = &comparisons[0];
+        assert_eq!(good.source, "Good");
+        assert_eq!(good.theirs.games, 2);
+        assert_eq!(good.theirs.correct, 2);
+        assert!((good.theirs.bits - (2.0 + 0.75f64.log2() + 0.6f64.log2())).abs() < TOLERANCE);
+        assert_eq!(good.theirs.total_abs_error, 10);
+        assert_eq!(good.ours.correct, 1);
+
+        let muddled = &comparisons[1];
+        assert_eq!(muddled.source, "Muddled");
+        assert_eq!(muddled.theirs.correct, 1);
+        assert!((muddled.theirs.bits - (1.0 + 0.75f64.log2())).abs() < TOLERANCE);
+
+        let bad = &comparisons[2];
+        assert_eq!(bad.theirs.games, 1);
+        assert_eq!(bad.theirs.correct, 0);
+        assert_eq!(bad.theirs.total_abs_error, 32);
+        assert_eq!(bad.ours.games, 1);
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+
+/// Errors raised while fetching, parsing and modelling match data
+#[derive(Debug)]
+pub enum Error {
+    /// Match data could not be retrieved from its source
+    Fetch(String),
+    /// Match data was retrieved but could not be parsed
+    Parse(String),
+    /// A team was referenced that the model has no ratings for
+    UnknownTeam(String),
+    /// A match is missing teams or scores needed to use it (Squiggle game id)
+    IncompleteMatch(i32),
+    /// An optimiser failed to produce a solution
+    Optimiser(String),
+    /// A model parameter is outside the range it is defined for
+    InvalidParameter(String),
+    /// Reading or writing a local file failed
+    Io(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Fetch(msg) => write!(f, "failed to fetch match data: {}", msg),
+            Error::Parse(msg) => write!(f, "failed to parse match data: {}", msg),
+            Error::UnknownTeam(team) => write!(f, "unknown team: {}", team),
+            Error::IncompleteMatch(id) => write!(f, "match {} is missing teams or scores", id),
+            Error::Optimiser(msg) => write!(f, "optimiser failed: {}", msg),
+            Error::InvalidParameter(msg) => write!(f, "invalid parameter: {}", msg),
+            Error::Io(msg) => write!(f, "i/o error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Parse(err.to_string())
+    }
+}
+
+impl From<chrono::ParseError> for Error {
+    fn from(err: chrono::ParseError) -> Self {
+        Error::Parse(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err.to_string())
+    }
+}
+
+impl From<argmin::core::Error> for Error {
+    fn from(err: argmin::core::Error) -> Self {
+        Error::Optimiser(err.to_string())
+    }
+}
@@ -0,0 +1,199 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::{
+    error::{Error, Result},
+    tipping::SquiggleMatch,
+};
+
+const POINTS_FOR_WIN: u32 = 4;
+const POINTS_FOR_DRAW: u32 = 2;
+
+/// A team's line on the ladder
+#[derive(Debug, Clone, PartialEq)]
+pub struct LadderEntry {
+    pub team: String,
+    pub played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub points_for: u32,
+    pub points_against: u32,
+}
+
+impl LadderEntry {
+    pub fn new(team: &str) -> LadderEntry {
+        LadderEntry {
+            team: team.to_string(),
+            played: 0,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            points_for: 0,
+            points_against: 0,
+        }
+    }
+
+    pub fn add_result(&mut self, score_for: u32, score_against: u32) {
+        self.played += 1;
+        match score_for.cmp(&score_against) {
+            Ordering::Greater => self.wins += 1,
+            Ordering::Equal => self.draws += 1,
+            Ordering::Less => self.losses += 1,
+        }
+        self.points_for += score_for;
+        self.points_against += score_against;
+    }
+
+    pub fn premiership_points(&self) -> u32 {
+        POINTS_FOR_WIN * self.wins + POINTS_FOR_DRAW * self.draws
+    }
+
+    /// Points for as a percentage of points against
+    pub fn percentage(&self) -> f64 {
+        if self.points_against == 0 {
+            return if self.points_for == 0 {
+                0.0
+            } else {
+                f64::INFINITY
+            };
+        }
+        100.0 * self.points_for as f64 / self.points_against as f64
+    }
+}
+
+/// The home-and-away ladder, ranked on premiership points then percentage
+#[derive(Debug, Clone, Default)]
+pub struct Ladder {
+    entries: HashMap<String, LadderEntry>,
+}
+
+impl Ladder {
+    pub fn new() -> Ladder {
+        Ladder::default()
+    }
+
+    /// Build the ladder from the completed home-and-away games of a season, up to and
+    /// including `up_to_round` if given. Every team with a fixture is listed, even
+    /// before it has played.
+    pub fn from_matches(matches: &[SquiggleMatch], up_to_round: Option<i32>) -> Result<Ladder> {
+        let mut ladder = Ladder::new();
+        for game in matches.iter().filter(|x| x.is_final == 0) {
+            let (Some(home_team), Some(away_team)) = (&game.hteam, &game.ateam) else {
+                continue;
+            };
+            ladder.add_team(home_team);
+            ladder.add_team(away_team);
+            if up_to_round.is_some_and(|round| game.round > round) {
+                continue;
+            }
+            if let Some(scores) = completed_scores(game)? {
+                ladder.add_result(home_team, away_team, scores);
+            }
+        }
+        Ok(ladder)
+    }
+
+    pub fn add_team(&mut self, team: &str) {
+        self.entries
+            .entry(team.to_string())
+            .or_insert_with(|| LadderEntry::new(team));
+    }
+
+    pub fn add_result(&mut self, home_team: &str, away_team: &str, scores: (u32, u32)) {
+        self.add_team(home_team);
+        self.add_team(away_team);
+        if let Some(entry) = self.entries.get_mut(home_team) {
+            entry.add_result(scores.0, scores.1);
+        }
+        if let Some(entry) = self.entries.get_mut(away_team) {
+            entry.add_result(scores.1, scores.0);
+        }
+    }
+
+    pub fn get(&self, team: &str) -> Option<&LadderEntry> {
+        self.entries.get(team)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries in ladder order, with ties that percentage cannot split broken by name
+    pub fn standings(&self) -> Vec<&LadderEntry> {
+        let mut entries: Vec<_> = self.entries.values().collect();
+        entries.sort_by(|a, b| {
+            b.premiership_points()
+                .cmp(&a.premiership_points())
+                .then(
+                    b.percentage()
+                        .partial_cmp(&a.percentage())
+                        .unwrap_or(Ordering::Equal),
+                )
+                .then(a.team.cmp(&b.team))
+        });
+        entries
+    }
+}
+
+/// Final scores of a game that has finished, or `None` if it is still to be played
+pub fn completed_scores(game: &SquiggleMatch) -> Result<Option<(u32, u32)>> {
+    if game.timestr != Some("Full Time".to_string()) {
+        return Ok(None);
+    }
+    let (Some(hscore), Some(ascore)) = (game.hscore, game.ascore) else {
+        return Err(Error::IncompleteMatch(game.id));
+    };
+    Ok(Some((hscore.max(0) as u32, ascore.max(0) as u32)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::game;
+
+    const TOLERANCE: f64 = 0.000_001;
+
+    #[test]
+    fn test_ladder_order() {
+        let mut ladder = Ladder::new();
+        ladder.add_result("A", "B", (100, 50));
+        ladder.add_result("C", "D", (80, 70));
+        ladder.add_result("B", "D", (60, 60));
+
+        let b = ladder.get("B").unwrap();
+        assert_eq!((b.played, b.wins, b.losses, b.draws), (2, 0, 1, 1));
+        assert_eq!(b.premiership_points(), 2);
+        assert!((ladder.get("C").unwrap().percentage() - 800.0 / 7.0).abs() < TOLERANCE);
+        // Percentage separates A from C and D from B
+        let order: Vec<_> = ladder.standings().iter().map(|x| x.team.clone()).collect();
+        assert_eq!(order, vec!["A", "C", "D", "B"]);
+    }
+
+    #[test]
+    fn test_ladder_from_matches() {
+        let mut final_ = game(5, 2024, 3, ("A", "C"), Some((50, 100)));
+        final_.is_final = 3;
+        let matches = vec![
+            game(1, 2024, 1, ("A", "B"), Some((100, 50))),
+            game(2, 2024, 1, ("C", "D"), Some((80, 70))),
+            game(3, 2024, 2, ("B", "C"), Some((90, 60))),
+            game(4, 2024, 2, ("D", "A"), None),
+            final_,
+        ];
+
+        let round_one = Ladder::from_matches(&matches, Some(1)).unwrap();
+        assert_eq!(round_one.len(), 4);
+        assert_eq!(round_one.get("B").unwrap().played, 1);
+
+        let ladder = Ladder::from_matches(&matches, None).unwrap();
+        let a = ladder.get("A").unwrap();
+        assert_eq!((a.played, a.points_for, a.points_against), (1, 100, 50));
+        assert_eq!(ladder.get("C").unwrap().losses, 1);
+        assert_eq!(ladder.standings()[0].team, "A");
+    }
+}
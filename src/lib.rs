@@ -1,36 +1,72 @@
-pub mod tipping;
+pub mod backtest;
+pub mod benchmark;
+pub mod error;
+pub mod ladder;
 mod optimise;
 pub mod presentation;
+pub mod simulation;
+pub mod tipping;
 
-use crate::tipping::models::glicko::GlickoModel;
-pub use optimise::optimise;
-use tipping::models::margin::MarginModel;
-
-use std::collections::HashMap;
-use tipping::models::glicko::{predict, update, GlickoModelInitParams};
+pub use error::{Error, Result};
+pub use optimise::{optimise, optimise_silent};
+use tipping::models::calibration::Calibration;
+use tipping::models::margin::{MarginMethod, MarginModel, RegressionMarginModel};
+use tipping::models::total::TotalModel;
+use tipping::models::{RatingModel, SeasonRegression};
 
 use tipping::{
-    squiggle::{get_squiggle_season, get_squiggle_teams},
-    MatchTipping, ModelPerformance, SquiggleMatch,
+    source::MatchSource, teams::TeamId, MatchEvaluation, MatchTipping, ModelPerformance,
 };
 
-fn tip_season(
-    tipping_matches: Vec<SquiggleMatch>,
-    mut model: GlickoModel,
+/// Options controlling how `run_model` trains the model before tipping
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Number of seasons before `year` to train on (defaults to 1)
+    pub warmup_seasons: Option<u32>,
+    /// Regression applied at each season boundary (defaults to none)
+    pub season_regression: Option<SeasonRegression>,
+    /// Where tip margins come from (defaults to `MarginMethod::Probability`)
+    pub margin_method: Option<MarginMethod>,
+}
+
+/// Margin in favour of the tipped team, or 0 if the margin favours the other side
+fn tipped_margin(home_margin: f64, home_team_win: bool) -> u32 {
+    let margin = if home_team_win {
+        home_margin
+    } else {
+        -home_margin
+    };
+    margin.max(0.0).round() as u32
+}
+
+/// Tip and evaluate `year` round by round. Tip margins come from the model's predicted
+/// scores when it has them, then `regression_margin` when given, and from `margin_model`
+/// otherwise. `total_model` splits margins into scores for models that do not predict them.
+pub async fn tip_season<S: MatchSource, M: RatingModel>(
+    year: i32,
+    source: &S,
+    mut model: M,
     mut margin_model: MarginModel,
-) -> (
-    GlickoModel,
+    mut total_model: TotalModel,
+    mut regression_margin: Option<RegressionMarginModel>,
+    calibration: &Calibration,
+) -> Result<(
+    M,
     MarginModel,
     ModelPerformance,
     Vec<MatchTipping>,
-) {
+    Vec<MatchEvaluation>,
+)> {
     let mut total = 0;
     let mut num_games = 0;
     let mut error_margin = 0;
     let mut mae = 0;
     let mut bits = 0.0;
+    let mut crps = 0.0;
     let mut tips: Vec<MatchTipping> = vec![];
-    for round in 0..tipping_matches.iter().map(|x| x.round).max().unwrap() + 1 {
+    let mut evaluations: Vec<MatchEvaluation> = vec![];
+    let tipping_matches = source.get_season(year).await?;
+    for round in 0..tipping_matches.iter().map(|x| x.round).max().unwrap_or(-1) + 1 {
         let round_matches = tipping_matches.iter().filter(|x| x.round == round);
         let round_over = round_matches
             .clone()
@@ -38,21 +74,56 @@ fn tip_season(
         let round_started = round_matches.clone().any(|x| x.timestr.is_some());
         let mut first_game = true;
         for game in round_matches {
-            let mut p = predict(&model, &game.get_match(), None);
-            p.pred_margin = margin_model.predict(p.prediction.max(1f64 - p.prediction));
+            // Finals fixtures are listed before their teams are known
+            if game.hteam.is_none() || game.ateam.is_none() {
+                continue;
+            }
+            let game_match = game.get_match()?;
+            let mut p = model.predict(&game_match)?;
+            p.prediction = calibration.calibrate(p.prediction);
+            p.home_team_win = p.prediction >= 0.5;
+            let rating_diff = regression_margin
+                .as_ref()
+                .map(|_| model.rating_difference(&game_match))
+                .transpose()?;
+            // A model's own distribution is recentred on the calibrated probability
+            let margin_distribution = match (p.margin_distribution, &regression_margin, rating_diff)
+            {
+                (Some(distribution), _, _) => distribution.with_home_probability(p.prediction),
+                (None, Some(regression), Some(diff)) => regression.predict(diff),
+                _ => margin_model.distribution(p.prediction),
+            };
+            // Predicted scores keep their total but move to the distribution's margin
+            if let (Some(home_score), Some(away_score)) = (p.home_score, p.away_score) {
+                let shift = (margin_distribution.mean - (home_score - away_score)) / 2.0;
+                p.home_score = Some(home_score + shift);
+                p.away_score = Some(away_score - shift);
+            }
+            p.pred_margin = match (p.home_score, p.away_score) {
+                (Some(home_score), Some(away_score)) => {
+                    tipped_margin(home_score.round() - away_score.round(), p.home_team_win)
+                }
+                _ if p.margin_distribution.is_some() || regression_margin.is_some() => {
+                    tipped_margin(margin_distribution.mean, p.home_team_win)
+                }
+                _ => margin_model.predict(p.confidence()),
+            };
 
             let predicted_winner = if p.home_team_win {
-                game.hteam.as_ref().unwrap()
+                &game_match.home_team
             } else {
-                game.ateam.as_ref().unwrap()
+                &game_match.away_team
             };
             let correct = predicted_winner == game.winner.as_ref().unwrap_or(predicted_winner);
-            let scaled_pred =
-                ((p.prediction.max(1.0f64 - p.prediction) - 0.5) * 1.2 + 0.5).min(1.0);
+            let scaled_pred = p.confidence();
 
             if game.timestr == Some("Full Time".to_string()) {
-                let game_result = &game.get_match_result();
-                model = update(model, &game.get_match(), game_result);
+                let game_result = &game.get_match_result()?;
+                model.update(&game_match, game_result)?;
+                if let (Some(regression), Some(diff)) = (&mut regression_margin, rating_diff) {
+                    regression.add_result(diff, game_result.home_margin());
+                }
+                total_model.add_result(game_result.home_points + game_result.away_points);
                 margin_model.add_result(
                     scaled_pred,
                     game_result.winning_margin.unwrap_or(0),
@@ -60,37 +131,20 @@ fn tip_season(
                 );
 
                 if round_over {
+                    let evaluation = MatchEvaluation::new(game, &game_match, &p, game_result);
                     num_games += 1;
-                    if game_result.draw {
+                    if evaluation.correct {
                         total += 1;
-                        let pred_error = p.pred_margin as i64;
-                        mae += pred_error;
-                        bits += 1.0 + 0.5 * (scaled_pred * (1.0 - scaled_pred)).log(2f64);
-                        if first_game {
-                            error_margin += pred_error;
-                        }
-                    } else if correct {
-                        total += 1;
-                        let pred_error = (p.pred_margin as i64
-                            - game_result.winning_margin.unwrap_or(0) as i64)
-                            .abs();
-                        mae += pred_error;
-                        bits += 1.0 + scaled_pred.log(2f64);
-                        if first_game {
-                            error_margin += pred_error;
-                        }
-                    } else {
-                        let pred_error = (p.pred_margin as i64
-                            + game_result.winning_margin.unwrap_or(0) as i64)
-                            .abs();
-                        mae += pred_error;
-                        bits += 1.0 + (1.0 - scaled_pred).log(2f64);
-                        if first_game {
-                            error_margin += pred_error;
-                        }
-                    };
+                    }
+                    mae += evaluation.abs_error as i64;
+                    bits += evaluation.bits;
+                    crps += margin_distribution.crps(evaluation.actual_margin as f64);
+                    if first_game {
+                        error_margin += evaluation.abs_error as i64;
+                    }
+                    evaluations.push(evaluation);
                     if margin_model.data.probs.len() > 25 {
-                        margin_model.update();
+                        margin_model.update()?;
                     }
                     first_game = false;
                     continue;
@@ -98,13 +152,30 @@ fn tip_season(
             }
             if !round_over || !round_started {
                 let w = if p.prediction >= 0.5 { 'H' } else { 'A' };
+                let (home_score, away_score) = match (p.home_score, p.away_score) {
+                    (Some(home_score), Some(away_score)) => (home_score, away_score),
+                    _ => {
+                        let home_margin = if p.home_team_win {
+                            p.pred_margin as f64
+                        } else {
+                            -(p.pred_margin as f64)
+                        };
+                        total_model.predict_scores(home_margin)
+                    }
+                };
+                let (home_score, away_score) =
+                    (home_score.round().max(0.0), away_score.round().max(0.0));
                 tips.push(MatchTipping {
                     home_or_away_wins: w,
                     winner: predicted_winner.to_string(),
                     margin: p.pred_margin,
                     percent: scaled_pred * 100.0,
-                    home_team_name: game.hteam.as_ref().unwrap().to_string(),
-                    away_team_name: game.ateam.as_ref().unwrap().to_string(),
+                    home_team_name: game_match.home_team.to_string(),
+                    away_team_name: game_match.away_team.to_string(),
+                    home_score: home_score as u32,
+                    away_score: away_score as u32,
+                    total: (home_score + away_score) as u32,
+                    margin_distribution,
                 });
             }
         }
@@ -112,7 +183,7 @@ fn tip_season(
             break;
         };
     }
-    (
+    Ok((
         model,
         margin_model,
         ModelPerformance {
@@ -121,48 +192,332 @@ fn tip_season(
             error_margin,
             mae,
             bits,
+            crps,
         },
         tips,
-    )
+        evaluations,
+    ))
 }
 
-pub async fn run_model(
+/// Train `model` on the seasons before `year`, then tip and evaluate `year`. The model's
+/// probabilities are calibrated against its predictions for the warm-up seasons, and the
+/// fitted calibration is returned for use elsewhere, such as `simulate_season`.
+pub async fn run_model<S: MatchSource, M: RatingModel>(
     year: i32,
-    cache_name: Option<String>,
-    offsets: Option<HashMap<String, f64>>,
-    user_agent: String,
-) -> (
-    GlickoModel,
+    source: &S,
+    mut model: M,
+    options: RunOptions,
+) -> Result<(
+    M,
     MarginModel,
+    Calibration,
     ModelPerformance,
     Vec<MatchTipping>,
-) {
-    let cache = cache_name.unwrap_or("squiggle_cache".to_string());
-    let warmup_matches = get_squiggle_season(year - 1, user_agent.clone(), cache.clone()).await;
-    let tipping_matches = get_squiggle_season(year, user_agent, cache).await;
-    let teams = get_squiggle_teams(&warmup_matches);
-
-    let params = GlickoModelInitParams {
-        teams,
-        starting_volatility: None,
-        starting_rd: None,
-        offsets,
-        scale_factor: None,
-        starting_elo: None,
-        volatility_constraint: None,
+    Vec<MatchEvaluation>,
+)> {
+    let warmup_seasons = options.warmup_seasons.unwrap_or(1) as i32;
+    for team_year in (year - warmup_seasons)..=year {
+        for team in source.get_teams(team_year).await? {
+            model.add_team(TeamId::from_squiggle(Some(team.id)), &team.name);
+        }
+    }
+    let mut warmup_matches = vec![];
+    for warmup_year in (year - warmup_seasons)..year {
+        warmup_matches.push(backtest::chronological(
+            source.get_season(warmup_year).await?,
+        )?);
+    }
+
+    let margin_model = MarginModel::new(None);
+    let mut total_model = TotalModel::new(None);
+    let mut regression_margin = match options.margin_method.unwrap_or_default() {
+        MarginMethod::Probability => None,
+        MarginMethod::Regression => Some(RegressionMarginModel::new()),
     };
 
-    let mut model = GlickoModel::new(params);
+    let mut calibration_samples = vec![];
+    for season in warmup_matches {
+        for game in season {
+            if game.timestr != Some("Full Time".to_string()) {
+                continue;
+            }
+            let match_obj = game.get_match()?;
+            let match_result = game.get_match_result()?;
+            let prediction = model.predict(&match_obj)?;
+            calibration_samples.push((prediction.prediction, match_result.home_outcome()));
+            total_model.add_result(match_result.home_points + match_result.away_points);
+            if let Some(regression) = &mut regression_margin {
+                regression.add_result(
+                    model.rating_difference(&match_obj)?,
+                    match_result.home_margin(),
+                );
+            }
+            model.update(&match_obj, &match_result)?;
+        }
+        // Every warm-up season ends at a boundary, including the one before `year`
+        if let Some(regression) = &options.season_regression {
+            model.new_season(regression);
+        }
+    }
 
-    let margin_model = MarginModel::new(None);
+    let calibration = Calibration::fit(&calibration_samples);
+    let (model, margin_model, perf, tips, evaluations) = tip_season(
+        year,
+        source,
+        model,
+        margin_model,
+        total_model,
+        regression_margin,
+        &calibration,
+    )
+    .await?;
+    Ok((model, margin_model, calibration, perf, tips, evaluations))
+}
 
-    for game in warmup_matches {
-        let match_obj = game.get_match();
-        let match_result = game.get_match_result();
-        {
-            model = update(model, &match_obj, &match_result);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::{
+        game,
+        models::attack_defence::{AttackDefenceModel, AttackDefenceModelInitParams},
+        models::glicko::{GlickoModel, GlickoModelInitParams},
+        source::VecSource,
+        SquiggleMatch,
+    };
+    use futures::executor::block_on;
+    use std::collections::HashSet;
+
+    fn glicko() -> GlickoModel {
+        GlickoModel::new(GlickoModelInitParams {
+            teams: HashSet::new(),
+            starting_rd: None,
+            starting_volatility: None,
+            starting_elo: None,
+            offsets: None,
+            scale_factor: None,
+            volatility_constraint: None,
+            rating_period_days: None,
+            home_advantage: None,
+            venue_offsets: None,
+            outcome: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_run_model_offline() {
+        let mut matches = vec![];
+        for round in 1..10 {
+            matches.push(game(round, 2023, round, ("A", "B"), Some((200, 160))));
         }
+        matches.push(game(20, 2024, 0, ("B", "A"), Some((50, 90))));
+        matches.push(game(21, 2024, 1, ("A", "B"), None));
+        let source = VecSource::new(matches);
+
+        let (model, _, _, perf, tips, evaluations) =
+            block_on(run_model(2024, &source, glicko(), RunOptions::default())).unwrap();
+
+        assert!(model.stats("A").unwrap().elo > model.stats("B").unwrap().elo);
+        assert_eq!(perf.num_games, 1);
+        assert_eq!(perf.total, 1);
+        assert_eq!(tips.len(), 1);
+        assert_eq!(evaluations.len(), 1);
+        assert_eq!(evaluations[0].game_id, 20);
+        assert_eq!(evaluations[0].actual_margin, -40);
+        assert!(evaluations[0].correct);
+        assert_eq!(tips[0].winner, "A");
+        assert_eq!(tips[0].home_or_away_wins, 'H');
+        assert!(tips[0].home_score > tips[0].away_score);
+        assert_eq!(tips[0].total, tips[0].home_score + tips[0].away_score);
+        // Totals are learnt from the warm-up season's high scores
+        assert!(tips[0].total > 200);
+    }
+
+    #[test]
+    fn test_calibrated_score_tips() {
+        let model = AttackDefenceModel::new(AttackDefenceModelInitParams {
+            teams: HashSet::from(["A".to_string(), "B".to_string()]),
+            mean_score: Some(80.0),
+            home_advantage: Some(20.0),
+            ..Default::default()
+        });
+        let source = VecSource::new(vec![game(1, 2024, 0, ("A", "B"), None)]);
+        // Biased enough against home teams to turn the model's home tip around
+        let calibration = Calibration {
+            slope: 1.0,
+            intercept: -2.0,
+        };
+
+        let (_, _, _, tips, _) = block_on(tip_season(
+            2024,
+            &source,
+            model,
+            MarginModel::new(None),
+            TotalModel::new(None),
+            None,
+            &calibration,
+        ))
+        .unwrap();
+
+        let tip = &tips[0];
+        assert_eq!(tip.winner, "B");
+        assert!(tip.margin_distribution.mean < 0.0);
+        let home_prob = tip.margin_distribution.prob_home_by_more_than(0.0);
+        assert!((home_prob - (1.0 - tip.percent / 100.0)).abs() < 1e-6);
+        assert_eq!(tip.margin, tip.away_score - tip.home_score);
+        assert_eq!(tip.total, 160);
     }
 
-    tip_season(tipping_matches, model, margin_model)
+    #[test]
+    fn test_regression_margins() {
+        let mut matches = vec![];
+        for round in 1..10 {
+            let (teams, scores) = if round % 2 == 0 {
+                (("A", "B"), (100, 60))
+            } else {
+                (("B", "A"), (60, 100))
+            };
+            matches.push(game(round, 2023, round, teams, Some(scores)));
+        }
+        matches.push(game(20, 2024, 0, ("A", "B"), None));
+        let source = VecSource::new(matches);
+        let options = RunOptions {
+            margin_method: Some(MarginMethod::Regression),
+            ..Default::default()
+        };
+
+        let (_, _, _, _, tips, _) = block_on(run_model(2024, &source, glicko(), options)).unwrap();
+
+        let distribution = tips[0].margin_distribution;
+        assert_eq!(tips[0].winner, "A");
+        assert!(distribution.mean > 0.0);
+        assert_eq!(tips[0].margin, distribution.mean.round() as u32);
+    }
+
+    #[test]
+    fn test_run_model_season_regression() {
+        let mut matches = vec![];
+        for round in 1..10 {
+            matches.push(game(round, 2022, round, ("A", "B"), Some((100, 60))));
+        }
+        matches.push(game(10, 2023, 1, ("A", "B"), Some((60, 100))));
+        matches.push(game(20, 2024, 1, ("A", "B"), None));
+        let source = VecSource::new(matches.clone());
+        let regression = SeasonRegression {
+            rating_shrink: 0.5,
+            rd_inflation: 0.5,
+        };
+        let run = |warmup_seasons, season_regression| {
+            let options = RunOptions {
+                warmup_seasons: Some(warmup_seasons),
+                season_regression,
+                ..Default::default()
+            };
+            block_on(run_model(2024, &source, glicko(), options))
+                .unwrap()
+                .0
+        };
+
+        // Replay both warm-up seasons by hand, regressing after each
+        let mut expected = glicko();
+        expected.add_team(None, "A");
+        expected.add_team(None, "B");
+        for year in [2022, 2023] {
+            for game in matches.iter().filter(|x| x.year == Some(year)) {
+                let result = game.get_match_result().unwrap();
+                expected
+                    .update(&game.get_match().unwrap(), &result)
+                    .unwrap();
+            }
+            expected.new_season(&regression);
+        }
+
+        let regressed = run(2, Some(regression));
+        for team in ["A", "B"] {
+            let (ours, theirs) = (
+                regressed.stats(team).unwrap(),
+                expected.stats(team).unwrap(),
+            );
+            assert!((ours.elo - theirs.elo).abs() < 1e-9);
+            assert!((ours.rd - theirs.rd).abs() < 1e-9);
+        }
+        let gap =
+            |model: &GlickoModel| model.stats("A").unwrap().elo - model.stats("B").unwrap().elo;
+        let unregressed = run(2, None);
+        assert!(gap(&unregressed) > gap(&regressed));
+        assert!(gap(&regressed) > 0.0);
+        // With one warm-up season only B's 2023 win is seen
+        assert!(gap(&run(1, None)) < 0.0);
+    }
+
+    #[test]
+    fn test_warmup_skips_unplayed_games_and_replays_in_date_order() {
+        let mut played = vec![];
+        for round in 1..10 {
+            let scores = if round < 5 { (100, 60) } else { (60, 100) };
+            played.push(game(round, 2023, round, ("A", "B"), Some(scores)));
+        }
+        let tipped = game(20, 2024, 1, ("A", "B"), None);
+        let mut unscheduled = game(11, 2023, 10, ("A", "B"), None);
+        unscheduled.hteam = None;
+        let unplayed = game(12, 2023, 10, ("B", "A"), None);
+
+        let mut shuffled: Vec<_> = played.iter().rev().cloned().collect();
+        shuffled.extend([unscheduled, unplayed, tipped.clone()]);
+        let mut ordered = played;
+        ordered.push(tipped);
+        let run = |matches| {
+            block_on(run_model(
+                2024,
+                &VecSource::new(matches),
+                glicko(),
+                RunOptions::default(),
+            ))
+            .unwrap()
+            .0
+        };
+
+        let (ours, expected) = (run(shuffled), run(ordered));
+        for team in ["A", "B"] {
+            let (ours, theirs) = (ours.stats(team).unwrap(), expected.stats(team).unwrap());
+            assert!((ours.elo - theirs.elo).abs() < 1e-9);
+            assert!((ours.rd - theirs.rd).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_renamed_team_keeps_its_ratings() {
+        let with_ids = |mut game: SquiggleMatch, home_id, away_id| {
+            game.hteamid = Some(home_id);
+            game.ateamid = Some(away_id);
+            game
+        };
+        let mut matches = vec![];
+        for round in 1..10 {
+            let game = game(round, 2023, round, ("Old Name", "B"), Some((100, 60)));
+            matches.push(with_ids(game, 30, 31));
+        }
+        let renamed = game(20, 2024, 0, ("B", "New Name"), Some((70, 90)));
+        matches.push(with_ids(renamed, 31, 30));
+        let source = VecSource::new(matches);
+
+        let (model, _, _, _, _, evaluations) =
+            block_on(run_model(2024, &source, glicko(), RunOptions::default())).unwrap();
+
+        assert_eq!(model.model_stats.len(), 2);
+        assert_eq!(model.registry.id("New Name"), Some(TeamId(30)));
+        assert_eq!(evaluations[0].tip, "New Name");
+        assert!(model.stats("Old Name").unwrap().elo > model.stats("B").unwrap().elo);
+    }
+
+    #[test]
+    fn test_null_scores_are_an_error() {
+        let mut unscored = game(2, 2024, 0, ("A", "B"), None);
+        unscored.timestr = Some("Full Time".to_string());
+        let source = VecSource::new(vec![game(1, 2023, 1, ("A", "B"), Some((80, 70))), unscored]);
+
+        let res = block_on(run_model(2024, &source, glicko(), RunOptions::default()));
+
+        assert!(matches!(res, Err(Error::IncompleteMatch(2))));
+    }
 }
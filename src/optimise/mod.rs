@@ -1,34 +1,39 @@
 use std::collections::HashMap;
 use std::iter::zip;
 
-use argmin::core::{CostFunction, Error, Executor};
+use argmin::core::{CostFunction, Executor};
 use argmin::solver::particleswarm::ParticleSwarm;
 use futures::executor::block_on;
 
-use crate::run_model;
+use crate::error::{Error, Result};
+use crate::{run_model, RunOptions};
+use crate::tipping::models::RatingModel;
+use crate::tipping::source::MatchSource;
 
 /// Optimization cost function that evaluates team offset parameters
 /// by running predictions across multiple seasons
-struct TotalScore {
+struct TotalScore<S: MatchSource, F> {
     year: i32,
-    user_agent: String,
+    source: S,
+    build_model: F,
     team_list: Vec<String>,
     show_progress: bool,
 }
 
-impl Clone for TotalScore {
+impl<S: MatchSource + Clone, F: Clone> Clone for TotalScore<S, F> {
     fn clone(&self) -> Self {
         Self {
             year: self.year,
-            user_agent: self.user_agent.clone(),
+            source: self.source.clone(),
+            build_model: self.build_model.clone(),
             team_list: self.team_list.clone(),
             show_progress: self.show_progress,
         }
     }
 }
 
-impl TotalScore {
-    fn construct_offsets(&self, offsets: &Vec<f64>) -> HashMap<String, f64> {
+impl<S: MatchSource, F> TotalScore<S, F> {
+    fn construct_offsets(&self, offsets: &[f64]) -> HashMap<String, f64> {
         let mut offset_map = HashMap::new();
         for (x, z) in zip(self.team_list.iter(), offsets.iter()) {
             offset_map.insert(x.to_string(), *z);
@@ -37,41 +42,64 @@ impl TotalScore {
     }
 }
 
-impl CostFunction for TotalScore {
+impl<S, M, F> CostFunction for TotalScore<S, F>
+where
+    S: MatchSource,
+    M: RatingModel,
+    F: Fn(HashMap<String, f64>) -> Result<M>,
+{
     type Param = Vec<f64>;
     type Output = f64;
 
-    fn cost(&self, param: &Self::Param) -> Result<Self::Output, Error> {
+    fn cost(&self, param: &Self::Param) -> std::result::Result<Self::Output, argmin::core::Error> {
         let offsets = self.construct_offsets(param);
-        let (_, _, perf1, _) = block_on(run_model(self.year-1, None, Some(offsets.clone()), self.user_agent.clone()));
-        let (_, _, perf2, _) = block_on(run_model(self.year-2, None, Some(offsets.clone()), self.user_agent.clone()));
-        let (_, _, perf3, _) = block_on(run_model(self.year-3, None, Some(offsets.clone()), self.user_agent.clone()));
+        let (_, _, _, perf1, _, _) = block_on(run_model(self.year-1, &self.source, (self.build_model)(offsets.clone())?, RunOptions::default()))?;
+        let (_, _, _, perf2, _, _) = block_on(run_model(self.year-2, &self.source, (self.build_model)(offsets.clone())?, RunOptions::default()))?;
+        let (_, _, _, perf3, _, _) = block_on(run_model(self.year-3, &self.source, (self.build_model)(offsets.clone())?, RunOptions::default()))?;
         if self.show_progress {
             print!(".");
             use std::io::{self, Write};
             io::stdout().flush().unwrap();
         }
-        Ok(-(perf1.bits + perf2.bits + perf3.bits) as f64)
+        Ok(-(perf1.bits + perf2.bits + perf3.bits))
     }
 }
 
-/// Optimize team offsets using particle swarm optimization with progress indicators
-pub fn optimise(year: i32, team_list: Vec<String>, user_agent: String) -> HashMap<String, f64> {
-    optimise_with_progress(year, team_list, user_agent, true)
+/// Optimize team offsets using particle swarm optimization with progress indicators.
+/// `build_model` constructs the model to evaluate from a candidate set of offsets, and may
+/// reject parameters the model cannot use.
+pub fn optimise<S, M, F>(year: i32, team_list: Vec<String>, source: S, build_model: F) -> Result<HashMap<String, f64>>
+where
+    S: MatchSource + Clone,
+    M: RatingModel,
+    F: Fn(HashMap<String, f64>) -> Result<M> + Clone,
+{
+    optimise_with_progress(year, team_list, source, build_model, true)
 }
 
 /// Optimize team offsets silently (no progress output)
-pub fn optimise_silent(year: i32, team_list: Vec<String>, user_agent: String) -> HashMap<String, f64> {
-    optimise_with_progress(year, team_list, user_agent, false)
+pub fn optimise_silent<S, M, F>(year: i32, team_list: Vec<String>, source: S, build_model: F) -> Result<HashMap<String, f64>>
+where
+    S: MatchSource + Clone,
+    M: RatingModel,
+    F: Fn(HashMap<String, f64>) -> Result<M> + Clone,
+{
+    optimise_with_progress(year, team_list, source, build_model, false)
 }
 
-fn optimise_with_progress(
+fn optimise_with_progress<S, M, F>(
     year: i32, 
     team_list: Vec<String>, 
-    user_agent: String,
+    source: S,
+    build_model: F,
     show_progress: bool
-) -> HashMap<String, f64> {
-    let cost_function = TotalScore { year, user_agent, team_list, show_progress };
+) -> Result<HashMap<String, f64>>
+where
+    S: MatchSource + Clone,
+    M: RatingModel,
+    F: Fn(HashMap<String, f64>) -> Result<M> + Clone,
+{
+    let cost_function = TotalScore { year, source, build_model, team_list, show_progress };
 
     let lb: Vec<f64> = [0.0_f64; 18].to_vec();
     let ub: Vec<f64> = [30.0_f64; 18].to_vec();
@@ -80,8 +108,11 @@ fn optimise_with_progress(
 
     let res = Executor::new(cost_function.clone(), solver)
         .configure(|state| state.max_iters(1000))
-        .run()
-        .unwrap();
+        .run()?;
 
-    cost_function.construct_offsets(&res.state.best_individual.unwrap().position)
+    let best = res
+        .state
+        .best_individual
+        .ok_or(Error::Optimiser("particle swarm found no solution".to_string()))?;
+    Ok(cost_function.construct_offsets(&best.position))
 }
@@ -1,6 +1,11 @@
-use crate::tipping::{
-    models::{glicko::GlickoModel, margin::MarginModel},
-    MatchTipping, ModelPerformance,
+use crate::{
+    benchmark::SourceComparison,
+    ladder::Ladder,
+    simulation::SeasonSimulation,
+    tipping::{
+        models::{margin::MarginModel, RatingModel, TeamRating},
+        MatchEvaluation, MatchTipping, ModelPerformance,
+    },
 };
 use std::collections::HashMap;
 
@@ -25,8 +30,8 @@ impl Presenter {
         Self::new(Box::new(ConsoleFormatter::new()))
     }
     
-    pub fn display_model_summary(&self, model: &GlickoModel) {
-        self.formatter.format_model_summary(model);
+    pub fn display_model_summary<M: RatingModel>(&self, model: &M) {
+        self.formatter.format_model_summary(&model.team_ratings());
     }
     
     pub fn display_tips(&self, tips: &[MatchTipping]) {
@@ -46,6 +51,26 @@ impl Presenter {
         self.formatter.format_offsets(offsets);
     }
     
+    pub fn display_evaluations(&self, evaluations: &[MatchEvaluation]) {
+        self.formatter.format_evaluations(evaluations);
+    }
+    
+    pub fn display_probability_report(&self, report: &ProbabilityReport) {
+        self.formatter.format_probability_report(report);
+    }
+    
+    pub fn display_benchmark(&self, comparisons: &[SourceComparison]) {
+        self.formatter.format_benchmark(comparisons);
+    }
+    
+    pub fn display_ladder(&self, ladder: &Ladder) {
+        self.formatter.format_ladder(ladder);
+    }
+    
+    pub fn display_season_simulation(&self, simulation: &SeasonSimulation) {
+        self.formatter.format_season_simulation(simulation);
+    }
+    
     pub fn show_optimization_progress(&self) {
         self.formatter.show_progress();
     }
@@ -53,9 +78,14 @@ impl Presenter {
 
 /// Trait for different output formats (console, JSON, etc.)
 pub trait OutputFormatter {
-    fn format_model_summary(&self, model: &GlickoModel);
+    fn format_model_summary(&self, ratings: &[TeamRating]);
     fn format_tips(&self, tips: &[MatchTipping]);
     fn format_performance_summary(&self, year: i32, performance: &ModelPerformance, margin_model: &MarginModel);
     fn format_offsets(&self, offsets: &HashMap<String, f64>);
+    fn format_ladder(&self, ladder: &Ladder);
+    fn format_evaluations(&self, evaluations: &[MatchEvaluation]);
+    fn format_probability_report(&self, report: &ProbabilityReport);
+    fn format_benchmark(&self, comparisons: &[SourceComparison]);
+    fn format_season_simulation(&self, simulation: &SeasonSimulation);
     fn show_progress(&self);
 }
\ No newline at end of file
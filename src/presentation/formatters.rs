@@ -1,7 +1,12 @@
-use super::OutputFormatter;
-use crate::tipping::{
-    models::{glicko::GlickoModel, margin::MarginModel},
-    MatchTipping, ModelPerformance,
+use super::{OutputFormatter, ProbabilityReport};
+use crate::{
+    benchmark::SourceComparison,
+    ladder::Ladder,
+    simulation::SeasonSimulation,
+    tipping::{
+        models::{margin::MarginModel, TeamRating},
+        MatchEvaluation, MatchTipping, ModelPerformance,
+    },
 };
 use std::collections::HashMap;
 
@@ -31,23 +36,23 @@ impl Default for ConsoleFormatter {
 }
 
 impl OutputFormatter for ConsoleFormatter {
-    fn format_model_summary(&self, model: &GlickoModel) {
+    fn format_model_summary(&self, ratings: &[TeamRating]) {
         println!("=== AFL Model Team Rankings ===");
-        let mut teams: Vec<_> = model.model_stats.iter().collect();
-        teams.sort_by(|(_, a), (_, b)| b.elo.partial_cmp(&a.elo).unwrap());
+        let mut teams: Vec<_> = ratings.iter().collect();
+        teams.sort_by(|a, b| b.rating.total_cmp(&a.rating));
         
-        for (rank, (team_name, stats)) in teams.iter().enumerate() {
+        for (rank, stats) in teams.iter().enumerate() {
             if self.show_detailed_stats {
-                println!(
-                    "{:2}. {:20} | ELO: {:7.1} | RD: {:5.1} | Vol: {:.3}",
-                    rank + 1,
-                    team_name,
-                    stats.elo,
-                    stats.rd,
-                    stats.volatility
-                );
+                let mut line = format!("{:2}. {:20} | ELO: {:7.1}", rank + 1, stats.team, stats.rating);
+                if let Some(rd) = stats.rd {
+                    line.push_str(&format!(" | RD: {:5.1}", rd));
+                }
+                if let Some(volatility) = stats.volatility {
+                    line.push_str(&format!(" | Vol: {:.3}", volatility));
+                }
+                println!("{}", line);
             } else {
-                println!("{:2}. {}: {:.1}", rank + 1, team_name, stats.elo);
+                println!("{:2}. {}: {:.1}", rank + 1, stats.team, stats.rating);
             }
         }
         println!();
@@ -62,13 +67,17 @@ impl OutputFormatter for ConsoleFormatter {
         println!("=== Match Predictions ===");
         for tip in tips {
             println!(
-                "({}) {} by {} pts ({:.1}%): {} v {}",
+                "({}) {} by {} pts ({:.1}%): {} v {} [{}-{}, total {}, sd {:.1}]",
                 tip.home_or_away_wins,
                 tip.winner,
                 tip.margin,
                 tip.percent,
                 tip.home_team_name,
-                tip.away_team_name
+                tip.away_team_name,
+                tip.home_score,
+                tip.away_score,
+                tip.total,
+                tip.margin_distribution.sd
             );
         }
         println!();
@@ -107,6 +116,12 @@ impl OutputFormatter for ConsoleFormatter {
             "  Bits Score: {:.3}",
             performance.bits
         );
+        if let Some(mean_crps) = performance.mean_crps() {
+            println!(
+                "  Mean Margin CRPS: {:.2}",
+                mean_crps
+            );
+        }
         println!(
             "  Final Margin Model k: {:.1}",
             margin_model.k
@@ -117,7 +132,7 @@ impl OutputFormatter for ConsoleFormatter {
     fn format_offsets(&self, offsets: &HashMap<String, f64>) {
         println!("=== Optimized Team Offsets ===");
         let mut sorted_offsets: Vec<_> = offsets.iter().collect();
-        sorted_offsets.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+        sorted_offsets.sort_by(|(_, a), (_, b)| b.total_cmp(a));
         
         for (team, offset) in sorted_offsets {
             println!("{:20}: {:8.3}", team, offset);
@@ -125,6 +140,116 @@ impl OutputFormatter for ConsoleFormatter {
         println!();
     }
     
+    fn format_ladder(&self, ladder: &Ladder) {
+        println!("=== Ladder ===");
+        println!(
+            "{:>2}. {:20} | {:>2} | {:>2} | {:>2} | {:>2} | {:>5} | {:>5} | {:>6} | {:>3}",
+            "#", "Team", "P", "W", "L", "D", "For", "Agst", "%", "Pts"
+        );
+        for (rank, entry) in ladder.standings().iter().enumerate() {
+            println!(
+                "{:2}. {:20} | {:2} | {:2} | {:2} | {:2} | {:5} | {:5} | {:6.1} | {:3}",
+                rank + 1,
+                entry.team,
+                entry.played,
+                entry.wins,
+                entry.losses,
+                entry.draws,
+                entry.points_for,
+                entry.points_against,
+                entry.percentage(),
+                entry.premiership_points()
+            );
+        }
+        println!();
+    }
+    
+    fn format_season_simulation(&self, simulation: &SeasonSimulation) {
+        println!("=== Season Simulation ({} runs) ===", simulation.iterations);
+        println!(
+            "{:20} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6}",
+            "Team", "Points", "Top 8", "Top 4", "Minor", "GF", "Flag"
+        );
+        for team in &simulation.teams {
+            println!(
+                "{:20} | {:6.1} | {:5.1}% | {:5.1}% | {:5.1}% | {:5.1}% | {:5.1}%",
+                team.team,
+                team.mean_premiership_points,
+                team.top_8 * 100.0,
+                team.top_4 * 100.0,
+                team.minor_premiership * 100.0,
+                team.grand_final * 100.0,
+                team.premiership * 100.0
+            );
+        }
+        println!();
+    }
+    
+    fn format_evaluations(&self, evaluations: &[MatchEvaluation]) {
+        println!("=== Match Evaluations ===");
+        for evaluation in evaluations {
+            let mark = if evaluation.correct { ' ' } else { 'x' };
+            println!(
+                "{} {} R{:2} {:20} v {:20} | tip {} by {} ({:.1}%) | actual {:+} | error {:3} | bits {:+.3}",
+                mark,
+                evaluation.year,
+                evaluation.round,
+                evaluation.home_team,
+                evaluation.away_team,
+                evaluation.tip,
+                evaluation.predicted_margin,
+                evaluation.probability * 100.0,
+                evaluation.actual_margin,
+                evaluation.abs_error,
+                evaluation.bits
+            );
+        }
+        println!();
+    }
+    
+    fn format_probability_report(&self, report: &ProbabilityReport) {
+        println!("=== Probability Scores ({} games) ===", report.games);
+        if let (Some(brier_score), Some(log_loss)) = (report.brier_score, report.log_loss) {
+            println!("  Brier Score: {:.4}", brier_score);
+            println!("  Log Loss: {:.4}", log_loss);
+        }
+        println!("  Calibration:");
+        for bucket in &report.calibration {
+            println!(
+                "    {:3.0}-{:3.0}% | {:4} games | predicted {:5.1}% | observed {:5.1}%",
+                bucket.lower * 100.0,
+                bucket.upper * 100.0,
+                bucket.games,
+                bucket.mean_predicted * 100.0,
+                bucket.observed_rate * 100.0
+            );
+        }
+        println!();
+    }
+    
+    fn format_benchmark(&self, comparisons: &[SourceComparison]) {
+        println!("=== Squiggle Benchmark ===");
+        println!(
+            "{:20} | {:>5} | {:>13} | {:>15} | {:>13}",
+            "Source", "Games", "Tips (ours)", "Bits (ours)", "MAE (ours)"
+        );
+        for comparison in comparisons {
+            let (ours, theirs) = (&comparison.ours, &comparison.theirs);
+            println!(
+                "{:20} | {:5} | {:5} ({:5}) | {:6.2} ({:6.2}) | {:5.2} ({:5.2})",
+                comparison.source,
+                theirs.games,
+                theirs.correct,
+                ours.correct,
+                theirs.bits,
+                ours.bits,
+                theirs.mean_abs_error(),
+                ours.mean_abs_error()
+            );
+        }
+        println!();
+    }
+    
     fn show_progress(&self) {
         print!(".");
         use std::io::{self, Write};
@@ -148,20 +273,24 @@ impl Default for JsonFormatter {
 }
 
 impl OutputFormatter for JsonFormatter {
-    fn format_model_summary(&self, model: &GlickoModel) {
-        let mut teams: Vec<_> = model.model_stats.iter().collect();
-        teams.sort_by(|(_, a), (_, b)| b.elo.partial_cmp(&a.elo).unwrap());
+    fn format_model_summary(&self, ratings: &[TeamRating]) {
+        let mut teams: Vec<_> = ratings.iter().collect();
+        teams.sort_by(|a, b| b.rating.total_cmp(&a.rating));
         
         println!("{{");
         println!("  \"model_summary\": {{");
         println!("    \"teams\": [");
         
-        for (i, (team_name, stats)) in teams.iter().enumerate() {
+        for (i, stats) in teams.iter().enumerate() {
             let comma = if i < teams.len() - 1 { "," } else { "" };
-            println!(
-                "      {{\"name\": \"{}\", \"elo\": {:.1}, \"rd\": {:.1}, \"volatility\": {:.3}}}{}",
-                team_name, stats.elo, stats.rd, stats.volatility, comma
-            );
+            let mut fields = format!("\"name\": \"{}\", \"elo\": {:.1}", stats.team, stats.rating);
+            if let Some(rd) = stats.rd {
+                fields.push_str(&format!(", \"rd\": {:.1}", rd));
+            }
+            if let Some(volatility) = stats.volatility {
+                fields.push_str(&format!(", \"volatility\": {:.3}", volatility));
+            }
+            println!("      {{{}}}{}", fields, comma);
         }
         
         println!("    ]");
@@ -176,8 +305,10 @@ impl OutputFormatter for JsonFormatter {
         for (i, tip) in tips.iter().enumerate() {
             let comma = if i < tips.len() - 1 { "," } else { "" };
             println!(
-                "    {{\"winner\": \"{}\", \"margin\": {}, \"confidence\": {:.1}, \"home\": \"{}\", \"away\": \"{}\"}}{}",
-                tip.winner, tip.margin, tip.percent, tip.home_team_name, tip.away_team_name, comma
+                "    {{\"winner\": \"{}\", \"margin\": {}, \"confidence\": {:.1}, \"home\": \"{}\", \"away\": \"{}\", \"home_score\": {}, \"away_score\": {}, \"total\": {}, \"margin_mean\": {:.1}, \"margin_sd\": {:.1}}}{}",
+                tip.winner, tip.margin, tip.percent, tip.home_team_name, tip.away_team_name,
+                tip.home_score, tip.away_score, tip.total,
+                tip.margin_distribution.mean, tip.margin_distribution.sd, comma
             );
         }
         
@@ -203,6 +334,9 @@ impl OutputFormatter for JsonFormatter {
         println!("    \"first_round_margin_error\": {},", performance.error_margin);
         println!("    \"mean_absolute_error\": {:.2},", mean_mae);
         println!("    \"bits_score\": {:.3},", performance.bits);
+        if let Some(mean_crps) = performance.mean_crps() {
+            println!("    \"mean_crps\": {:.2},", mean_crps);
+        }
         println!("    \"margin_model_k\": {:.1}", margin_model.k);
         println!("  }}");
         println!("}}");
@@ -222,7 +356,240 @@ impl OutputFormatter for JsonFormatter {
         println!("}}");
     }
     
+    fn format_ladder(&self, ladder: &Ladder) {
+        let standings = ladder.standings();
+        
+        println!("{{");
+        println!("  \"ladder\": [");
+        
+        for (i, entry) in standings.iter().enumerate() {
+            let comma = if i < standings.len() - 1 { "," } else { "" };
+            println!(
+                "    {{\"position\": {}, \"team\": \"{}\", \"played\": {}, \"wins\": {}, \"losses\": {}, \"draws\": {}, \"points_for\": {}, \"points_against\": {}, \"percentage\": {:.2}, \"premiership_points\": {}}}{}",
+                i + 1, entry.team, entry.played, entry.wins, entry.losses, entry.draws,
+                entry.points_for, entry.points_against, entry.percentage(),
+                entry.premiership_points(), comma
+            );
+        }
+        
+        println!("  ]");
+        println!("}}");
+    }
+    
+    fn format_season_simulation(&self, simulation: &SeasonSimulation) {
+        println!("{{");
+        println!("  \"season_simulation\": {{");
+        println!("    \"iterations\": {},", simulation.iterations);
+        println!("    \"teams\": [");
+        
+        for (i, team) in simulation.teams.iter().enumerate() {
+            let comma = if i < simulation.teams.len() - 1 { "," } else { "" };
+            let positions: Vec<String> = team.positions.iter().map(|p| format!("{:.4}", p)).collect();
+            println!(
+                "      {{\"name\": \"{}\", \"mean_points\": {:.2}, \"top_8\": {:.4}, \"top_4\": {:.4}, \"minor_premiership\": {:.4}, \"grand_final\": {:.4}, \"premiership\": {:.4}, \"positions\": [{}]}}{}",
+                team.team, team.mean_premiership_points, team.top_8, team.top_4,
+                team.minor_premiership, team.grand_final, team.premiership,
+                positions.join(", "), comma
+            );
+        }
+        
+        println!("    ]");
+        println!("  }}");
+        println!("}}");
+    }
+    
+    fn format_evaluations(&self, evaluations: &[MatchEvaluation]) {
+        println!("{{");
+        println!("  \"evaluations\": [");
+        
+        for (i, evaluation) in evaluations.iter().enumerate() {
+            let comma = if i < evaluations.len() - 1 { "," } else { "" };
+            println!(
+                "    {{\"game_id\": {}, \"year\": {}, \"round\": {}, \"home\": \"{}\", \"away\": \"{}\", \"tip\": \"{}\", \"probability\": {:.4}, \"predicted_margin\": {}, \"actual_margin\": {}, \"correct\": {}, \"bits\": {:.4}, \"abs_error\": {}}}{}",
+                evaluation.game_id, evaluation.year, evaluation.round, evaluation.home_team,
+                evaluation.away_team, evaluation.tip, evaluation.probability,
+                evaluation.predicted_margin, evaluation.actual_margin, evaluation.correct,
+                evaluation.bits, evaluation.abs_error, comma
+            );
+        }
+        
+        println!("  ]");
+        println!("}}");
+    }
+    
+    fn format_probability_report(&self, report: &ProbabilityReport) {
+        println!("{{");
+        println!("  \"probability_scores\": {{");
+        println!("    \"games\": {},", report.games);
+        if let (Some(brier_score), Some(log_loss)) = (report.brier_score, report.log_loss) {
+            println!("    \"brier_score\": {:.4},", brier_score);
+            println!("    \"log_loss\": {:.4},", log_loss);
+        }
+        println!("    \"calibration\": [");
+        
+        for (i, bucket) in report.calibration.iter().enumerate() {
+            let comma = if i < report.calibration.len() - 1 { "," } else { "" };
+            println!(
+                "      {{\"lower\": {:.2}, \"upper\": {:.2}, \"games\": {}, \"mean_predicted\": {:.4}, \"observed_rate\": {:.4}}}{}",
+                bucket.lower, bucket.upper, bucket.games, bucket.mean_predicted,
+                bucket.observed_rate, comma
+            );
+        }
+        
+        println!("    ]");
+        println!("  }}");
+        println!("}}");
+    }
+    
+    fn format_benchmark(&self, comparisons: &[SourceComparison]) {
+        println!("{{");
+        println!("  \"benchmark\": [");
+        
+        for (i, comparison) in comparisons.iter().enumerate() {
+            let comma = if i < comparisons.len() - 1 { "," } else { "" };
+            let (ours, theirs) = (&comparison.ours, &comparison.theirs);
+            println!(
+                "    {{\"source\": \"{}\", \"games\": {}, \"correct\": {}, \"bits\": {:.3}, \"mae\": {:.2}, \"our_correct\": {}, \"our_bits\": {:.3}, \"our_mae\": {:.2}}}{}",
+                comparison.source, theirs.games, theirs.correct, theirs.bits,
+                theirs.mean_abs_error(), ours.correct, ours.bits, ours.mean_abs_error(), comma
+            );
+        }
+        
+        println!("  ]");
+        println!("}}");
+    }
+    
     fn show_progress(&self) {
         // JSON formatter doesn't show progress dots
     }
-}
\ No newline at end of file
+}
+
+/// CSV formatter, one header row followed by a row per item, for spreadsheets
+pub struct CsvFormatter;
+
+impl CsvFormatter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CsvFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputFormatter for CsvFormatter {
+    fn format_model_summary(&self, ratings: &[TeamRating]) {
+        println!("team,rating,rd,volatility");
+        for stats in ratings {
+            let rd = stats.rd.map(|x| format!("{:.1}", x)).unwrap_or_default();
+            let volatility = stats.volatility.map(|x| format!("{:.4}", x)).unwrap_or_default();
+            println!("{},{:.1},{},{}", stats.team, stats.rating, rd, volatility);
+        }
+    }
+    
+    fn format_tips(&self, tips: &[MatchTipping]) {
+        println!("home,away,winner,margin,confidence,home_score,away_score,total,margin_sd");
+        for tip in tips {
+            println!(
+                "{},{},{},{},{:.1},{},{},{},{:.1}",
+                tip.home_team_name, tip.away_team_name, tip.winner, tip.margin, tip.percent,
+                tip.home_score, tip.away_score, tip.total, tip.margin_distribution.sd
+            );
+        }
+    }
+    
+    fn format_performance_summary(
+        &self,
+        year: i32,
+        performance: &ModelPerformance,
+        margin_model: &MarginModel,
+    ) {
+        println!("year,correct_tips,total_games,first_round_margin_error,mean_absolute_error,bits_score,mean_crps,margin_model_k");
+        println!(
+            "{},{},{},{},{:.2},{:.3},{},{:.1}",
+            year,
+            performance.total,
+            performance.num_games,
+            performance.error_margin,
+            performance.mae as f64 / performance.num_games as f64,
+            performance.bits,
+            performance
+                .mean_crps()
+                .map(|x| format!("{:.2}", x))
+                .unwrap_or_default(),
+            margin_model.k
+        );
+    }
+    
+    fn format_offsets(&self, offsets: &HashMap<String, f64>) {
+        println!("team,offset");
+        for (team, offset) in offsets {
+            println!("{},{:.3}", team, offset);
+        }
+    }
+    
+    fn format_ladder(&self, ladder: &Ladder) {
+        println!("position,team,played,wins,losses,draws,points_for,points_against,percentage,premiership_points");
+        for (i, entry) in ladder.standings().iter().enumerate() {
+            println!(
+                "{},{},{},{},{},{},{},{},{:.2},{}",
+                i + 1, entry.team, entry.played, entry.wins, entry.losses, entry.draws,
+                entry.points_for, entry.points_against, entry.percentage(),
+                entry.premiership_points()
+            );
+        }
+    }
+    
+    fn format_season_simulation(&self, simulation: &SeasonSimulation) {
+        println!("team,mean_points,top_8,top_4,minor_premiership,grand_final,premiership");
+        for team in &simulation.teams {
+            println!(
+                "{},{:.2},{:.4},{:.4},{:.4},{:.4},{:.4}",
+                team.team, team.mean_premiership_points, team.top_8, team.top_4,
+                team.minor_premiership, team.grand_final, team.premiership
+            );
+        }
+    }
+    
+    fn format_evaluations(&self, evaluations: &[MatchEvaluation]) {
+        println!("game_id,year,round,home,away,tip,probability,predicted_margin,actual_margin,correct,bits,abs_error");
+        for evaluation in evaluations {
+            println!(
+                "{},{},{},{},{},{},{:.4},{},{},{},{:.4},{}",
+                evaluation.game_id, evaluation.year, evaluation.round, evaluation.home_team,
+                evaluation.away_team, evaluation.tip, evaluation.probability,
+                evaluation.predicted_margin, evaluation.actual_margin, evaluation.correct,
+                evaluation.bits, evaluation.abs_error
+            );
+        }
+    }
+    
+    fn format_probability_report(&self, report: &ProbabilityReport) {
+        println!("lower,upper,games,mean_predicted,observed_rate");
+        for bucket in &report.calibration {
+            println!(
+                "{:.2},{:.2},{},{:.4},{:.4}",
+                bucket.lower, bucket.upper, bucket.games, bucket.mean_predicted,
+                bucket.observed_rate
+            );
+        }
+    }
+    
+    fn format_benchmark(&self, comparisons: &[SourceComparison]) {
+        println!("source,games,correct,bits,mae,our_correct,our_bits,our_mae");
+        for comparison in comparisons {
+            let (ours, theirs) = (&comparison.ours, &comparison.theirs);
+            println!(
+                "{},{},{},{:.3},{:.2},{},{:.3},{:.2}",
+                comparison.source, theirs.games, theirs.correct, theirs.bits,
+                theirs.mean_abs_error(), ours.correct, ours.bits, ours.mean_abs_error()
+            );
+        }
+    }
+    
+    fn show_progress(&self) {
+        // CSV output stays machine readable
+    }
+}
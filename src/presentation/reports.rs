@@ -1,8 +1,13 @@
 use crate::tipping::{
-    models::{glicko::GlickoModel, margin::MarginModel},
-    MatchTipping, ModelPerformance,
+    models::{margin::MarginModel, RatingModel},
+    MatchEvaluation, MatchTipping, ModelPerformance,
 };
 
+/// Width of each calibration bucket, in probability given to the tipped team
+const CALIBRATION_BUCKET_WIDTH: f64 = 0.05;
+/// Keeps log loss finite for tips made with certainty
+const PROBABILITY_FLOOR: f64 = 1e-6;
+
 /// Generates comprehensive analysis reports
 pub struct ReportGenerator;
 
@@ -12,11 +17,11 @@ impl ReportGenerator {
     }
     
     /// Generate a detailed model analysis report
-    pub fn generate_model_analysis(model: &GlickoModel) -> ModelAnalysisReport {
-        let mut teams: Vec<_> = model.model_stats.iter().collect();
-        teams.sort_by(|(_, a), (_, b)| b.elo.partial_cmp(&a.elo).unwrap());
+    pub fn generate_model_analysis<M: RatingModel>(model: &M) -> ModelAnalysisReport {
+        let mut teams = model.team_ratings();
+        teams.sort_by(|a, b| b.rating.total_cmp(&a.rating));
         
-        let elos: Vec<f64> = teams.iter().map(|(_, stats)| stats.elo).collect();
+        let elos: Vec<f64> = teams.iter().map(|stats| stats.rating).collect();
         let mean_elo = elos.iter().sum::<f64>() / elos.len() as f64;
         let elo_std = (elos.iter().map(|x| (x - mean_elo).powi(2)).sum::<f64>() / elos.len() as f64).sqrt();
         
@@ -26,8 +31,8 @@ impl ReportGenerator {
             lowest_elo: *elos.last().unwrap(),
             mean_elo,
             elo_standard_deviation: elo_std,
-            top_team: teams[0].0.clone(),
-            bottom_team: teams.last().unwrap().0.clone(),
+            top_team: teams[0].team.clone(),
+            bottom_team: teams.last().unwrap().team.clone(),
         }
     }
     
@@ -47,11 +52,67 @@ impl ReportGenerator {
             accuracy_rate: accuracy,
             mean_absolute_error: mean_mae,
             bits_score: performance.bits,
+            mean_crps: performance.mean_crps(),
             first_round_margin_error: performance.error_margin,
             margin_model_k: margin_model.k,
         }
     }
     
+    /// Generate Brier score, log loss and a calibration table from per-match predictions.
+    /// Probabilities are those given to the tipped team, and a draw counts as half a win.
+    pub fn generate_probability_report(evaluations: &[MatchEvaluation]) -> ProbabilityReport {
+        if evaluations.is_empty() {
+            return ProbabilityReport::empty();
+        }
+        
+        let num_buckets = (0.5 / CALIBRATION_BUCKET_WIDTH).round() as usize;
+        let mut buckets: Vec<CalibrationBucket> = (0..num_buckets)
+            .map(|i| CalibrationBucket {
+                lower: 0.5 + i as f64 * CALIBRATION_BUCKET_WIDTH,
+                upper: 0.5 + (i + 1) as f64 * CALIBRATION_BUCKET_WIDTH,
+                games: 0,
+                mean_predicted: 0.0,
+                observed_rate: 0.0,
+            })
+            .collect();
+        
+        let mut brier = 0.0;
+        let mut log_loss = 0.0;
+        for evaluation in evaluations {
+            let p = evaluation.probability.clamp(PROBABILITY_FLOOR, 1.0 - PROBABILITY_FLOOR);
+            let outcome = if evaluation.actual_margin == 0 {
+                0.5
+            } else if evaluation.correct {
+                1.0
+            } else {
+                0.0
+            };
+            brier += (p - outcome).powi(2);
+            log_loss -= outcome * p.ln() + (1.0 - outcome) * (1.0 - p).ln();
+            
+            // The small offset keeps probabilities on a boundary out of the bucket below
+            let index = (((p - 0.5) / CALIBRATION_BUCKET_WIDTH + 1e-9).floor().max(0.0) as usize)
+                .min(num_buckets - 1);
+            let bucket = &mut buckets[index];
+            bucket.games += 1;
+            bucket.mean_predicted += p;
+            bucket.observed_rate += outcome;
+        }
+        
+        for bucket in buckets.iter_mut().filter(|b| b.games > 0) {
+            bucket.mean_predicted /= bucket.games as f64;
+            bucket.observed_rate /= bucket.games as f64;
+        }
+        
+        let games = evaluations.len();
+        ProbabilityReport {
+            games,
+            brier_score: Some(brier / games as f64),
+            log_loss: Some(log_loss / games as f64),
+            calibration: buckets.into_iter().filter(|b| b.games > 0).collect(),
+        }
+    }
+    
     /// Generate tips analysis
     pub fn analyze_tips(tips: &[MatchTipping]) -> TipsAnalysisReport {
         if tips.is_empty() {
@@ -67,6 +128,7 @@ impl ReportGenerator {
         
         let mean_margin = margins.iter().sum::<u32>() as f64 / margins.len() as f64;
         let mean_confidence = confidences.iter().sum::<f64>() / confidences.len() as f64;
+        let mean_total = tips.iter().map(|t| t.total).sum::<u32>() as f64 / total_tips as f64;
         
         let high_confidence_tips = tips.iter().filter(|t| t.percent > 75.0).count();
         
@@ -76,7 +138,8 @@ impl ReportGenerator {
             away_wins,
             home_win_percentage: home_wins as f64 / total_tips as f64,
             mean_predicted_margin: mean_margin,
-            mean_confidence: mean_confidence,
+            mean_predicted_total: mean_total,
+            mean_confidence,
             high_confidence_tips,
             high_confidence_percentage: high_confidence_tips as f64 / total_tips as f64,
         }
@@ -108,10 +171,33 @@ pub struct PerformanceReport {
     pub accuracy_rate: f64,
     pub mean_absolute_error: f64,
     pub bits_score: f64,
+    /// Mean margin CRPS, if there were any games
+    pub mean_crps: Option<f64>,
     pub first_round_margin_error: i64,
     pub margin_model_k: f64,
 }
 
+#[derive(Debug)]
+pub struct ProbabilityReport {
+    pub games: usize,
+    /// Mean squared error of the tipped team's probability, if there were any games
+    pub brier_score: Option<f64>,
+    /// Mean negative log likelihood, in nats, if there were any games
+    pub log_loss: Option<f64>,
+    /// Buckets that contain at least one prediction, lowest probability first
+    pub calibration: Vec<CalibrationBucket>,
+}
+
+/// Predictions made with probabilities in `[lower, upper)`, and how often they came off
+#[derive(Debug, Clone)]
+pub struct CalibrationBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub games: usize,
+    pub mean_predicted: f64,
+    pub observed_rate: f64,
+}
+
 #[derive(Debug)]
 pub struct TipsAnalysisReport {
     pub total_tips: usize,
@@ -119,11 +205,23 @@ pub struct TipsAnalysisReport {
     pub away_wins: usize,
     pub home_win_percentage: f64,
     pub mean_predicted_margin: f64,
+    pub mean_predicted_total: f64,
     pub mean_confidence: f64,
     pub high_confidence_tips: usize,
     pub high_confidence_percentage: f64,
 }
 
+impl ProbabilityReport {
+    fn empty() -> Self {
+        Self {
+            games: 0,
+            brier_score: None,
+            log_loss: None,
+            calibration: Vec::new(),
+        }
+    }
+}
+
 impl TipsAnalysisReport {
     fn empty() -> Self {
         Self {
@@ -132,9 +230,70 @@ impl TipsAnalysisReport {
             away_wins: 0,
             home_win_percentage: 0.0,
             mean_predicted_margin: 0.0,
+            mean_predicted_total: 0.0,
             mean_confidence: 0.0,
             high_confidence_tips: 0,
             high_confidence_percentage: 0.0,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::evaluation;
+
+    const TOLERANCE: f64 = 0.000_001;
+
+    #[test]
+    fn test_probability_report() {
+        let evaluations = vec![
+            evaluation(1, 0.6, 12, true),
+            evaluation(1, 0.62, -5, false),
+            evaluation(1, 0.9, 40, true),
+            evaluation(1, 0.8, 0, true),
+        ];
+
+        let report = ReportGenerator::generate_probability_report(&evaluations);
+
+        let brier = (0.16 + 0.3844 + 0.01 + 0.09) / 4.0;
+        assert!((report.brier_score.unwrap() - brier).abs() < TOLERANCE);
+        let log_loss = -(0.6f64.ln() + 0.38f64.ln() + 0.9f64.ln() + 0.5 * (0.8f64 * 0.2).ln()) / 4.0;
+        assert!((report.log_loss.unwrap() - log_loss).abs() < TOLERANCE);
+
+        assert_eq!(report.calibration.len(), 3);
+        let first = &report.calibration[0];
+        assert!((first.lower - 0.6).abs() < TOLERANCE);
+        assert_eq!(first.games, 2);
+        assert!((first.mean_predicted - 0.61).abs() < TOLERANCE);
+        assert!((first.observed_rate - 0.5).abs() < TOLERANCE);
+        assert!((report.calibration[1].observed_rate - 0.5).abs() < TOLERANCE);
+
+        // Before any games are played there is nothing to score
+        let empty = ReportGenerator::generate_probability_report(&[]);
+        assert_eq!(empty.games, 0);
+        assert_eq!(empty.brier_score, None);
+        assert_eq!(empty.log_loss, None);
+        assert!(empty.calibration.is_empty());
+    }
+
+    #[test]
+    fn test_performance_report_without_games() {
+        let performance = ModelPerformance {
+            total: 0,
+            num_games: 0,
+            error_margin: 0,
+            mae: 0,
+            bits: 0.0,
+            crps: 0.0,
+        };
+
+        let report = ReportGenerator::generate_performance_report(
+            2024,
+            &performance,
+            &MarginModel::new(None),
+        );
+
+        assert_eq!(report.mean_crps, None);
+    }
+}
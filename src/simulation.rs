@@ -0,0 +1,478 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::NaiveDateTime;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    error::Result,
+    ladder::{completed_scores, Ladder},
+    tipping::{
+        models::{
+            calibration::Calibration,
+            distribution::{normal_quantile, MarginDistribution},
+            margin::MarginModel,
+            total::TotalModel,
+            RatingModel,
+        },
+        source::MatchSource,
+        Match, MatchPrediction, SquiggleMatch,
+    },
+};
+
+const DEFAULT_ITERATIONS: u32 = 10_000;
+const DEFAULT_TOTAL_SD: f64 = 25.0;
+const FINALS_TEAMS: usize = 8;
+
+/// Options controlling `simulate_season`
+#[derive(Debug, Clone, Default)]
+pub struct SimulationOptions {
+    /// Number of times to play out the season (defaults to 10,000)
+    pub iterations: Option<u32>,
+    /// Seed for the random number generator, for reproducible runs
+    pub seed: Option<u64>,
+    /// Spread of a simulated match's combined score (defaults to 25 points)
+    pub total_sd: Option<f64>,
+}
+
+/// How often a team finished in each part of the ladder across all simulations
+#[derive(Debug, Clone)]
+pub struct TeamSimulation {
+    pub team: String,
+    /// Probability of finishing in each ladder position, first place first
+    pub positions: Vec<f64>,
+    pub top_8: f64,
+    pub top_4: f64,
+    pub minor_premiership: f64,
+    pub mean_premiership_points: f64,
+    pub grand_final: f64,
+    pub premiership: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SeasonSimulation {
+    pub iterations: u32,
+    /// Teams ordered by their mean premiership points
+    pub teams: Vec<TeamSimulation>,
+}
+
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    normal_quantile(rng.gen_range(f64::EPSILON..1.0))
+}
+
+struct RemainingGame {
+    home_team: String,
+    away_team: String,
+    margin: MarginDistribution,
+    total: f64,
+}
+
+impl RemainingGame {
+    fn sample_scores(&self, total_sd: f64, rng: &mut StdRng) -> (u32, u32) {
+        let margin = self.margin.mean + self.margin.sd * standard_normal(rng);
+        let total = (self.total + total_sd * standard_normal(rng)).max(margin.abs());
+        (
+            ((total + margin) / 2.0).round().max(0.0) as u32,
+            ((total - margin) / 2.0).round().max(0.0) as u32,
+        )
+    }
+}
+
+/// `model`'s prediction for `match_` with its probability calibrated, and a margin
+/// distribution to match, recentred as `tip_season` does
+fn calibrated_prediction<M: RatingModel>(
+    model: &M,
+    margin_model: &MarginModel,
+    calibration: &Calibration,
+    match_: &Match,
+) -> Result<(MatchPrediction, MarginDistribution)> {
+    let mut p = model.predict(match_)?;
+    p.prediction = calibration.calibrate(p.prediction);
+    p.home_team_win = p.prediction >= 0.5;
+    let margin = match p.margin_distribution {
+        Some(distribution) => distribution.with_home_probability(p.prediction),
+        None => margin_model.distribution(p.prediction),
+    };
+    Ok((p, margin))
+}
+
+/// Winning chances in finals, which are not played out on the ladder
+struct FinalsPredictor<'a, M: RatingModel> {
+    model: &'a M,
+    margin_model: &'a MarginModel,
+    calibration: &'a Calibration,
+    date: NaiveDateTime,
+    /// Whether the first team won, for finals that have already been played, keyed by
+    /// finals week so a later rematch is still simulated
+    played: HashMap<(usize, String, String), bool>,
+    margins: HashMap<(String, String), MarginDistribution>,
+}
+
+impl<M: RatingModel> FinalsPredictor<'_, M> {
+    fn home_wins(&mut self, week: usize, home: &str, away: &str, rng: &mut StdRng) -> Result<bool> {
+        if let Some(home_won) = self.played.get(&(week, home.to_string(), away.to_string())) {
+            return Ok(*home_won);
+        }
+        let key = (home.to_string(), away.to_string());
+        let margin = match self.margins.get(&key) {
+            Some(margin) => *margin,
+            None => {
+                let (_, margin) = calibrated_prediction(
+                    self.model,
+                    self.margin_model,
+                    self.calibration,
+                    &Match {
+                        home_team: key.0.clone(),
+                        away_team: key.1.clone(),
+                        date: self.date,
+                        home_team_id: None,
+                        away_team_id: None,
+                        venue: None,
+                    },
+                )?;
+                self.margins.insert(key, margin);
+                margin
+            }
+        };
+        // Finals go to extra time rather than being drawn
+        Ok(margin.mean + margin.sd * standard_normal(rng) > 0.0)
+    }
+}
+
+/// Play the AFL final eight from a finished ladder, returning the ladder positions of the
+/// premier and the runner-up. The top four play qualifying finals with a second chance in
+/// the semi finals, while the elimination finals knock out the losers; the higher-placed
+/// team hosts each game. `home_wins` is given the finals week, from 1 to 4, of each game.
+fn play_finals<F>(ladder: &[String], mut home_wins: F) -> Result<Option<(usize, usize)>>
+where
+    F: FnMut(usize, &str, &str) -> Result<bool>,
+{
+    if ladder.len() < FINALS_TEAMS {
+        return Ok(None);
+    }
+    let mut play = |week: usize, home: usize, away: usize| -> Result<(usize, usize)> {
+        Ok(if home_wins(week, &ladder[home], &ladder[away])? {
+            (home, away)
+        } else {
+            (away, home)
+        })
+    };
+    let (qf1_winner, qf1_loser) = play(1, 0, 3)?;
+    let (qf2_winner, qf2_loser) = play(1, 1, 2)?;
+    let (ef1_winner, _) = play(1, 4, 7)?;
+    let (ef2_winner, _) = play(1, 5, 6)?;
+    let (sf1_winner, _) = play(2, qf1_loser, ef1_winner)?;
+    let (sf2_winner, _) = play(2, qf2_loser, ef2_winner)?;
+    let (pf1_winner, _) = play(3, qf1_winner, sf2_winner)?;
+    let (pf2_winner, _) = play(3, qf2_winner, sf1_winner)?;
+    let grand_final = play(4, pf1_winner.min(pf2_winner), pf1_winner.max(pf2_winner))?;
+    Ok(Some(grand_final))
+}
+
+/// Play out the unfinished home-and-away games of `matches` many times, predicting each
+/// with `model` and `calibration` as for tipping and sampling scores from its margin
+/// distribution, and tally where every team finishes on the ladder, then play the finals
+/// series from that ladder. Finals that have already been played keep their results when
+/// the same teams meet in the same week. Ratings are held fixed at their current values.
+pub fn simulate_season<M: RatingModel>(
+    matches: &[SquiggleMatch],
+    model: &M,
+    margin_model: &MarginModel,
+    calibration: &Calibration,
+    options: &SimulationOptions,
+) -> Result<SeasonSimulation> {
+    let iterations = options.iterations.unwrap_or(DEFAULT_ITERATIONS);
+    let total_sd = options.total_sd.unwrap_or(DEFAULT_TOTAL_SD);
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let mut ladder = Ladder::new();
+    let mut total_model = TotalModel::new(None);
+    let mut remaining = vec![];
+    let mut played_finals = HashMap::new();
+    // Finals weeks count up from the first finals round
+    let finals_rounds: BTreeSet<i32> = matches
+        .iter()
+        .filter(|x| x.is_final > 0)
+        .map(|x| x.round)
+        .collect();
+    let mut last_date = None;
+    for game in matches {
+        let (Some(home_team), Some(away_team)) = (&game.hteam, &game.ateam) else {
+            continue;
+        };
+        if game.is_final > 0 {
+            if let Some((hscore, ascore)) = completed_scores(game)? {
+                let week = finals_rounds.range(..game.round).count() + 1;
+                played_finals.insert(
+                    (week, home_team.clone(), away_team.clone()),
+                    hscore > ascore,
+                );
+                played_finals.insert(
+                    (week, away_team.clone(), home_team.clone()),
+                    ascore > hscore,
+                );
+            }
+            continue;
+        }
+        let date = game.get_match()?.date;
+        last_date = last_date.max(Some(date));
+        ladder.add_team(home_team);
+        ladder.add_team(away_team);
+        match completed_scores(game)? {
+            Some(scores) => {
+                ladder.add_result(home_team, away_team, scores);
+                total_model.add_result(scores.0 + scores.1);
+            }
+            None => remaining.push(game),
+        }
+    }
+
+    let remaining = remaining
+        .into_iter()
+        .map(|game| {
+            let (p, margin) =
+                calibrated_prediction(model, margin_model, calibration, &game.get_match()?)?;
+            let total = match (p.home_score, p.away_score) {
+                (Some(home_score), Some(away_score)) => home_score + away_score,
+                _ => total_model.mean_total,
+            };
+            Ok(RemainingGame {
+                home_team: game.hteam.clone().unwrap_or_default(),
+                away_team: game.ateam.clone().unwrap_or_default(),
+                margin,
+                total,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let num_teams = ladder.len();
+    let mut position_counts: HashMap<String, Vec<u32>> = ladder
+        .standings()
+        .iter()
+        .map(|entry| (entry.team.clone(), vec![0; num_teams]))
+        .collect();
+    let mut points_totals: HashMap<String, u64> = HashMap::new();
+    let mut grand_finals: HashMap<String, u32> = HashMap::new();
+    let mut premierships: HashMap<String, u32> = HashMap::new();
+    let mut finals = FinalsPredictor {
+        model,
+        margin_model,
+        calibration,
+        date: last_date.unwrap_or_default(),
+        played: played_finals,
+        margins: HashMap::new(),
+    };
+    for _ in 0..iterations {
+        let mut simulated = ladder.clone();
+        for game in &remaining {
+            let scores = game.sample_scores(total_sd, &mut rng);
+            simulated.add_result(&game.home_team, &game.away_team, scores);
+        }
+        let standings = simulated.standings();
+        for (position, entry) in standings.iter().enumerate() {
+            position_counts.get_mut(&entry.team).unwrap()[position] += 1;
+            *points_totals.entry(entry.team.clone()).or_default() +=
+                entry.premiership_points() as u64;
+        }
+        let order: Vec<String> = standings.into_iter().map(|x| x.team.clone()).collect();
+        if let Some((premier, runner_up)) = play_finals(&order, |week, home, away| {
+            finals.home_wins(week, home, away, &mut rng)
+        })? {
+            *premierships.entry(order[premier].clone()).or_default() += 1;
+            *grand_finals.entry(order[premier].clone()).or_default() += 1;
+            *grand_finals.entry(order[runner_up].clone()).or_default() += 1;
+        }
+    }
+
+    let n = iterations.max(1) as f64;
+    let mut teams: Vec<TeamSimulation> = position_counts
+        .into_iter()
+        .map(|(team, counts)| {
+            let positions: Vec<f64> = counts.iter().map(|x| *x as f64 / n).collect();
+            TeamSimulation {
+                top_8: positions.iter().take(8).sum(),
+                top_4: positions.iter().take(4).sum(),
+                minor_premiership: positions.first().copied().unwrap_or(0.0),
+                mean_premiership_points: *points_totals.get(&team).unwrap_or(&0) as f64 / n,
+                grand_final: *grand_finals.get(&team).unwrap_or(&0) as f64 / n,
+                premiership: *premierships.get(&team).unwrap_or(&0) as f64 / n,
+                positions,
+                team,
+            }
+        })
+        .collect();
+    teams.sort_by(|a, b| {
+        b.mean_premiership_points
+            .total_cmp(&a.mean_premiership_points)
+            .then(a.team.cmp(&b.team))
+    });
+
+    Ok(SeasonSimulation { iterations, teams })
+}
+
+/// Simulate the rest of `year`'s home-and-away season from `source`
+pub async fn simulate_remaining_season<S: MatchSource, M: RatingModel>(
+    year: i32,
+    source: &S,
+    model: &M,
+    margin_model: &MarginModel,
+    calibration: &Calibration,
+    options: &SimulationOptions,
+) -> Result<SeasonSimulation> {
+    let matches = source.get_season(year).await?;
+    simulate_season(&matches, model, margin_model, calibration, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::{
+        game,
+        models::elo::{EloModel, EloModelInitParams},
+    };
+    use std::collections::HashSet;
+
+    const TOLERANCE: f64 = 0.000_001;
+
+    #[test]
+    fn test_simulate_season() {
+        let matches = vec![
+            game(1, 2024, 1, ("A", "B"), Some((120, 40))),
+            game(2, 2024, 1, ("C", "D"), Some((90, 80))),
+            game(3, 2024, 2, ("A", "C"), None),
+            game(4, 2024, 2, ("B", "D"), None),
+        ];
+        let model = EloModel::new(EloModelInitParams {
+            teams: HashSet::from(["A", "B", "C", "D"].map(String::from)),
+            ..Default::default()
+        });
+        let options = SimulationOptions {
+            iterations: Some(2_000),
+            seed: Some(7),
+            total_sd: None,
+        };
+
+        let sim = simulate_season(
+            &matches,
+            &model,
+            &MarginModel::new(None),
+            &Calibration::identity(),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(sim.teams.len(), 4);
+        for team in &sim.teams {
+            assert!((team.positions.iter().sum::<f64>() - 1.0).abs() < TOLERANCE);
+            assert!((team.top_8 - 1.0).abs() < TOLERANCE);
+        }
+        let team = |name: &str| sim.teams.iter().find(|x| x.team == name).unwrap();
+        // A's percentage means it is only overtaken if it loses to C
+        assert!(team("A").minor_premiership > 0.5);
+        let minor_premierships: f64 = sim.teams.iter().map(|x| x.minor_premiership).sum();
+        assert!((minor_premierships - 1.0).abs() < TOLERANCE);
+
+        let again = simulate_season(
+            &matches,
+            &model,
+            &MarginModel::new(None),
+            &Calibration::identity(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(again.teams[0].positions, sim.teams[0].positions);
+
+        // A calibration that all but rules out home wins leaves A losing to C at home
+        let away_bias = Calibration {
+            slope: 1.0,
+            intercept: -5.0,
+        };
+        let biased = simulate_season(
+            &matches,
+            &model,
+            &MarginModel::new(None),
+            &away_bias,
+            &options,
+        )
+        .unwrap();
+        let biased_a = biased.teams.iter().find(|x| x.team == "A").unwrap();
+        assert!(biased_a.minor_premiership < team("A").minor_premiership);
+    }
+
+    #[test]
+    fn test_play_finals() {
+        let ladder: Vec<String> = (1..=8).map(|x| x.to_string()).collect();
+        let mut games = vec![];
+        let favourites = play_finals(&ladder, |week, home, away| {
+            games.push((week, home.to_string(), away.to_string()));
+            Ok(true)
+        });
+        assert_eq!(favourites.unwrap(), Some((0, 1)));
+        // Both semi finals are hosted by the qualifying final losers
+        assert_eq!(games[4], (2, "4".to_string(), "5".to_string()));
+        assert_eq!(games[5], (2, "3".to_string(), "6".to_string()));
+        assert_eq!(games[8].0, 4);
+        assert_eq!(games.len(), 9);
+
+        let roughies = play_finals(&ladder, |_, _, _| Ok(false)).unwrap();
+        assert_eq!(roughies, Some((7, 6)));
+        assert_eq!(play_finals(&ladder[..4], |_, _, _| Ok(true)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_simulate_finals() {
+        // One game each leaves a settled ladder from A down to H on percentage
+        let mut matches = vec![
+            game(1, 2024, 1, ("A", "H"), Some((150, 50))),
+            game(2, 2024, 1, ("B", "G"), Some((140, 60))),
+            game(3, 2024, 1, ("C", "F"), Some((130, 70))),
+            game(4, 2024, 1, ("D", "E"), Some((120, 80))),
+        ];
+        let finals = [
+            // D upsets A in a qualifying final, and A comes back through the semi finals
+            (5, ("A", "D"), Some((60, 80))),
+            (5, ("B", "C"), Some((90, 70))),
+            (5, ("E", "H"), Some((90, 70))),
+            (5, ("F", "G"), Some((90, 70))),
+            (6, ("A", "E"), Some((90, 70))),
+            (6, ("C", "F"), Some((90, 70))),
+            (7, ("D", "C"), Some((90, 70))),
+            (7, ("B", "A"), Some((70, 90))),
+            (8, ("A", "D"), None),
+        ];
+        for (i, (round, teams, scores)) in finals.into_iter().enumerate() {
+            let mut final_ = game(10 + i as i32, 2024, round, teams, scores);
+            final_.is_final = 2;
+            matches.push(final_);
+        }
+        let model = EloModel::new(EloModelInitParams {
+            teams: HashSet::from(["A", "B", "C", "D", "E", "F", "G", "H"].map(String::from)),
+            ..Default::default()
+        });
+        let options = SimulationOptions {
+            iterations: Some(1_000),
+            seed: Some(3),
+            total_sd: None,
+        };
+
+        let sim = simulate_season(
+            &matches,
+            &model,
+            &MarginModel::new(None),
+            &Calibration::identity(),
+            &options,
+        )
+        .unwrap();
+
+        let team = |name: &str| sim.teams.iter().find(|x| x.team == name).unwrap();
+        assert!((team("A").minor_premiership - 1.0).abs() < TOLERANCE);
+        assert!((team("A").grand_final - 1.0).abs() < TOLERANCE);
+        assert!((team("D").grand_final - 1.0).abs() < TOLERANCE);
+        // The grand final rematch is simulated rather than taken from the qualifying final
+        assert!(team("A").premiership > 0.2);
+        assert!(team("D").premiership > 0.2);
+        assert!((team("A").premiership + team("D").premiership - 1.0).abs() < TOLERANCE);
+    }
+}
@@ -1,14 +1,25 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    error::{Error, Result},
+    tipping::{models::distribution::MarginDistribution, teams::TeamId},
+};
+
 pub mod models;
+pub mod source;
 pub mod squiggle;
+pub mod teams;
+pub mod venues;
 
 #[derive(Debug)]
 pub struct Match {
-    home_team: String,
-    away_team: String,
-    date: chrono::NaiveDateTime,
-    venue: Option<String>,
+    pub home_team: String,
+    pub away_team: String,
+    pub date: chrono::NaiveDateTime,
+    /// Squiggle team IDs, when the source has them; models fall back to the names otherwise
+    pub home_team_id: Option<TeamId>,
+    pub away_team_id: Option<TeamId>,
+    pub venue: Option<String>,
 }
 
 #[derive(Debug)]
@@ -18,12 +29,71 @@ pub struct MatchResult {
     pub draw: bool,
     pub home_team_won: bool,
     pub away_team_won: bool,
+    /// Final points, which every source reports even without a goals/behinds breakdown
+    pub home_points: u32,
+    pub away_points: u32,
+    pub home_score: Option<Score>,
+    pub away_score: Option<Score>,
+}
+
+/// A team's goals and behinds in a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    pub goals: u32,
+    pub behinds: u32,
+}
+
+impl Score {
+    pub fn points(&self) -> u32 {
+        6 * self.goals + self.behinds
+    }
+
+    /// Scoring shots, counting every goal and behind
+    pub fn shots(&self) -> u32 {
+        self.goals + self.behinds
+    }
+}
+
+impl MatchResult {
+    /// Margin from the home team's perspective, negative when the away team won
+    pub fn home_margin(&self) -> i64 {
+        let margin = self.winning_margin.unwrap_or(0) as i64;
+        if self.home_team_won {
+            margin
+        } else {
+            -margin
+        }
+    }
+
+    /// 1 for a home win, 0.5 for a draw and 0 for an away win
+    pub fn home_outcome(&self) -> f64 {
+        if self.draw {
+            0.5
+        } else if self.home_team_won {
+            1.0
+        } else {
+            0.0
+        }
+    }
 }
 
 pub struct MatchPrediction {
     pub prediction: f64,
     pub pred_margin: u32,
     pub home_team_win: bool,
+    /// Expected home team score, for models that predict scores
+    pub home_score: Option<f64>,
+    /// Expected away team score, for models that predict scores
+    pub away_score: Option<f64>,
+    /// Distribution of the home team's margin, for models that predict one
+    pub margin_distribution: Option<MarginDistribution>,
+}
+
+impl MatchPrediction {
+    /// Probability given to the tipped team
+    pub fn confidence(&self) -> f64 {
+        self.prediction.max(1.0 - self.prediction)
+    }
 }
 
 pub struct MatchTipping {
@@ -33,6 +103,69 @@ pub struct MatchTipping {
     pub percent: f64,
     pub home_team_name: String,
     pub away_team_name: String,
+    pub home_score: u32,
+    pub away_score: u32,
+    pub total: u32,
+    pub margin_distribution: MarginDistribution,
+}
+
+/// How the prediction for a completed match fared
+#[derive(Debug, Clone)]
+pub struct MatchEvaluation {
+    pub game_id: i32,
+    pub year: i32,
+    pub round: i32,
+    pub home_team: String,
+    pub away_team: String,
+    pub tip: String,
+    /// Probability given to the tipped team
+    pub probability: f64,
+    /// Predicted margin in favour of the tipped team
+    pub predicted_margin: u32,
+    /// Actual margin from the home team's perspective
+    pub actual_margin: i64,
+    /// Whether the tipped team won; draws count as correct
+    pub correct: bool,
+    pub bits: f64,
+    pub abs_error: u32,
+}
+
+impl MatchEvaluation {
+    pub fn new(
+        game: &SquiggleMatch,
+        match_: &Match,
+        prediction: &MatchPrediction,
+        result: &MatchResult,
+    ) -> MatchEvaluation {
+        let probability = prediction.confidence();
+        let (tip, signed_margin) = if prediction.home_team_win {
+            (&match_.home_team, prediction.pred_margin as i64)
+        } else {
+            (&match_.away_team, -(prediction.pred_margin as i64))
+        };
+        let correct = result.draw || prediction.home_team_win == result.home_team_won;
+        let bits = if result.draw {
+            1.0 + 0.5 * (probability * (1.0 - probability)).log2()
+        } else if correct {
+            1.0 + probability.log2()
+        } else {
+            1.0 + (1.0 - probability).log2()
+        };
+        MatchEvaluation {
+            game_id: game.id,
+            year: game.year.unwrap_or_default(),
+            round: game.round,
+            home_team: match_.home_team.clone(),
+            away_team: match_.away_team.clone(),
+            tip: tip.clone(),
+            probability,
+            predicted_margin: prediction.pred_margin,
+            actual_margin: result.home_margin(),
+            correct,
+            bits,
+            abs_error: (signed_margin - result.home_margin()).unsigned_abs() as u32,
+        }
+    }
 }
 
 // Display logic moved to presentation module
@@ -50,7 +183,7 @@ impl PartialEq for Team {
 
 // Display logic moved to presentation module
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SquiggleMatch {
     pub abehinds: Option<i32>,
     pub agoals: Option<i32>,
@@ -81,37 +214,56 @@ pub struct SquiggleMatch {
 }
 
 impl SquiggleMatch {
-    pub fn get_match(&self) -> Match {
-        Match {
-            home_team: self.hteam.clone().unwrap(),
-            away_team: self.ateam.clone().unwrap(),
-            date: chrono::NaiveDateTime::parse_from_str(&self.localtime, "%Y-%m-%d %H:%M:%S")
-                .unwrap(),
+    pub fn get_match(&self) -> Result<Match> {
+        let (Some(home_team), Some(away_team)) = (self.hteam.clone(), self.ateam.clone()) else {
+            return Err(Error::IncompleteMatch(self.id));
+        };
+        Ok(Match {
+            home_team,
+            away_team,
+            date: chrono::NaiveDateTime::parse_from_str(&self.localtime, "%Y-%m-%d %H:%M:%S")?,
+            home_team_id: TeamId::from_squiggle(self.hteamid),
+            away_team_id: TeamId::from_squiggle(self.ateamid),
             venue: self.venue.clone(),
-        }
+        })
     }
 
-    pub fn get_match_result(&self) -> MatchResult {
-        let margin = if self.hscore == self.ascore {
+    pub fn get_match_result(&self) -> Result<MatchResult> {
+        let (Some(hscore), Some(ascore)) = (self.hscore, self.ascore) else {
+            return Err(Error::IncompleteMatch(self.id));
+        };
+        let margin = if hscore == ascore {
             None
         } else {
-            Some((self.hscore.unwrap() - self.ascore.unwrap()).unsigned_abs())
+            Some((hscore - ascore).unsigned_abs())
         };
-        let winning_team = if self.ascore.unwrap() == self.hscore.unwrap() {
+        let winning_team = if hscore == ascore {
             None
         } else {
             Some(Team {
-                name: self.winner.clone().unwrap(),
+                name: self.winner.clone().ok_or(Error::IncompleteMatch(self.id))?,
             })
         };
 
-        MatchResult {
+        let score = |goals: Option<i32>, behinds: Option<i32>| match (goals, behinds) {
+            (Some(goals), Some(behinds)) => Some(Score {
+                goals: goals.max(0) as u32,
+                behinds: behinds.max(0) as u32,
+            }),
+            _ => None,
+        };
+
+        Ok(MatchResult {
             winning_team,
             winning_margin: margin,
-            away_team_won: self.hscore.unwrap() < self.ascore.unwrap(),
-            home_team_won: self.hscore.unwrap() > self.ascore.unwrap(),
-            draw: self.hscore.unwrap() == self.ascore.unwrap(),
-        }
+            away_team_won: hscore < ascore,
+            home_team_won: hscore > ascore,
+            draw: hscore == ascore,
+            home_points: hscore.max(0) as u32,
+            away_points: ascore.max(0) as u32,
+            home_score: score(self.hgoals, self.hbehinds),
+            away_score: score(self.agoals, self.abehinds),
+        })
     }
 }
 
@@ -121,4 +273,96 @@ pub struct ModelPerformance {
     pub error_margin: i64,
     pub mae: i64,
     pub bits: f64,
+    /// Summed continuous ranked probability score of the margin distributions
+    pub crps: f64,
+}
+
+impl ModelPerformance {
+    /// Mean CRPS per game, if any games were tipped
+    pub fn mean_crps(&self) -> Option<f64> {
+        (self.num_games > 0).then(|| self.crps / self.num_games as f64)
+    }
+}
+
+/// A completed (with `scores`) or upcoming match for tests, dated by its round
+#[cfg(test)]
+pub(crate) fn game(
+    id: i32,
+    year: i32,
+    round: i32,
+    teams: (&str, &str),
+    scores: Option<(i32, i32)>,
+) -> SquiggleMatch {
+    let (hteam, ateam) = teams;
+    let winner = scores.and_then(|(h, a)| match h.cmp(&a) {
+        std::cmp::Ordering::Greater => Some(hteam.to_string()),
+        std::cmp::Ordering::Less => Some(ateam.to_string()),
+        std::cmp::Ordering::Equal => None,
+    });
+    SquiggleMatch {
+        abehinds: None,
+        agoals: None,
+        ascore: scores.map(|x| x.1),
+        ateam: Some(ateam.to_string()),
+        ateamid: None,
+        hbehinds: None,
+        hgoals: None,
+        hscore: scores.map(|x| x.0),
+        hteam: Some(hteam.to_string()),
+        hteamid: None,
+        complete: Some(if scores.is_some() { 100 } else { 0 }),
+        date: format!("{}-04-{:02} 19:40:00", year, round + 1),
+        id,
+        is_final: 0,
+        is_grand_final: 0,
+        localtime: format!("{}-04-{:02} 19:40:00", year, round + 1),
+        round,
+        roundname: Some(format!("Round {}", round)),
+        timestr: scores.map(|_| "Full Time".to_string()),
+        tz: "+10:00".to_string(),
+        unixtime: 0,
+        updated: None,
+        venue: None,
+        winner,
+        winnerteamid: None,
+        year: Some(year),
+    }
+}
+
+/// A match between `home` and `away` for model tests, at no particular venue
+#[cfg(test)]
+pub(crate) fn fixture(home: &str, away: &str) -> Match {
+    Match {
+        home_team: home.to_string(),
+        away_team: away.to_string(),
+        home_team_id: None,
+        away_team_id: None,
+        venue: None,
+        date: chrono::NaiveDateTime::parse_from_str("2024-04-01 10:10:10", "%Y-%m-%d %H:%M:%S")
+            .unwrap(),
+    }
+}
+
+/// An evaluation of a tip on the home team "A" against "B"
+#[cfg(test)]
+pub(crate) fn evaluation(
+    game_id: i32,
+    probability: f64,
+    actual_margin: i64,
+    correct: bool,
+) -> MatchEvaluation {
+    MatchEvaluation {
+        game_id,
+        year: 2024,
+        round: 1,
+        home_team: "A".to_string(),
+        away_team: "B".to_string(),
+        tip: "A".to_string(),
+        probability,
+        predicted_margin: 10,
+        actual_margin,
+        correct,
+        bits: 0.0,
+        abs_error: 0,
+    }
 }
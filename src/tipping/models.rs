@@ -1,2 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Result,
+    tipping::{models::distribution::logit, teams::TeamId, Match, MatchPrediction, MatchResult},
+};
+
+pub mod attack_defence;
+pub mod calibration;
+pub mod distribution;
+pub mod elo;
+pub mod ensemble;
 pub mod glicko;
 pub mod margin;
+pub mod scoring_shots;
+pub mod snapshot;
+pub mod total;
+
+/// A team's current strength as reported by a `RatingModel`
+#[derive(Debug, Clone)]
+pub struct TeamRating {
+    pub team: String,
+    pub rating: f64,
+    pub rd: Option<f64>,
+    pub volatility: Option<f64>,
+}
+
+/// Pull applied to ratings at the boundary between two seasons
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SeasonRegression {
+    /// Fraction of each team's distance from the mean rating removed (0 keeps, 1 resets)
+    pub rating_shrink: f64,
+    /// Fraction of the gap between each team's rating deviation and its starting value restored
+    pub rd_inflation: f64,
+}
+
+/// A model that rates teams from completed matches and predicts upcoming ones
+pub trait RatingModel {
+    /// Probability that the home team wins `match_`
+    fn predict(&self, match_: &Match) -> Result<MatchPrediction>;
+
+    /// How much stronger the home team is rated than the away team, on the model's own
+    /// scale; `RegressionMarginModel` regresses margins on it. Defaults to the log-odds of
+    /// the predicted home win probability.
+    fn rating_difference(&self, match_: &Match) -> Result<f64> {
+        Ok(logit(self.predict(match_)?.prediction))
+    }
+
+    /// Fold a completed match into the ratings
+    fn update(&mut self, match_: &Match, match_result: &MatchResult) -> Result<()>;
+
+    /// Current rating of every team, in no particular order
+    fn team_ratings(&self) -> Vec<TeamRating>;
+
+    /// Start rating `team` from the model's defaults if it has not been seen before. `id`
+    /// is the team's Squiggle ID where the source has one, so a club keeps its ratings
+    /// under a new display name.
+    fn add_team(&mut self, id: Option<TeamId>, team: &str);
+
+    /// Apply between-season regression ahead of a new season
+    fn new_season(&mut self, regression: &SeasonRegression);
+
+    /// Serialise the model's full state
+    fn to_json(&self) -> Result<String>;
+}
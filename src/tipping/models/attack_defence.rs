@@ -0,0 +1,242 @@
+use crate::{
+    error::{Error, Result},
+    tipping::{
+        models::{
+            distribution::{normal_cdf, MarginDistribution},
+            snapshot, RatingModel, SeasonRegression, TeamRating,
+        },
+        teams::TeamId,
+        Match, MatchPrediction, MatchResult,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A team's scoring strengths, in points relative to the league average
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackDefenceTeamStats {
+    /// Points scored above average
+    pub attack: f64,
+    /// Points conceded below average
+    pub defence: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackDefenceModel {
+    pub model_stats: HashMap<String, AttackDefenceTeamStats>,
+    pub model_params: AttackDefenceModelParams,
+}
+
+#[derive(Debug, Default)]
+pub struct AttackDefenceModelInitParams {
+    pub teams: HashSet<String>,
+    /// Starting league average score per team per match
+    pub mean_score: Option<f64>,
+    /// Home advantage in points of margin
+    pub home_advantage: Option<f64>,
+    /// Fraction of each scoring error applied to attack and defence
+    pub learning_rate: Option<f64>,
+    /// Fraction of each scoring error applied to the league average
+    pub mean_learning_rate: Option<f64>,
+    /// Standard deviation of the margin about its prediction
+    pub margin_sd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackDefenceModelParams {
+    pub teams: HashSet<String>,
+    pub mean_score: f64,
+    pub home_advantage: f64,
+    pub learning_rate: f64,
+    pub mean_learning_rate: f64,
+    pub margin_sd: f64,
+}
+
+/// Expected score for each side of a match
+#[derive(Debug, Clone, Copy)]
+pub struct ScorePrediction {
+    pub home_score: f64,
+    pub away_score: f64,
+}
+
+impl ScorePrediction {
+    pub fn total(&self) -> f64 {
+        self.home_score + self.away_score
+    }
+
+    pub fn margin(&self) -> f64 {
+        self.home_score - self.away_score
+    }
+}
+
+impl AttackDefenceModel {
+    pub fn new(params: AttackDefenceModelInitParams) -> AttackDefenceModel {
+        const DEFAULT_MEAN_SCORE: f64 = 82.0;
+        const DEFAULT_HOME_ADVANTAGE: f64 = 8.0;
+        const DEFAULT_LEARNING_RATE: f64 = 0.08;
+        const DEFAULT_MEAN_LEARNING_RATE: f64 = 0.005;
+        const DEFAULT_MARGIN_SD: f64 = 37.0;
+
+        let mut model = AttackDefenceModel {
+            model_stats: HashMap::new(),
+            model_params: AttackDefenceModelParams {
+                teams: HashSet::new(),
+                mean_score: params.mean_score.unwrap_or(DEFAULT_MEAN_SCORE),
+                home_advantage: params.home_advantage.unwrap_or(DEFAULT_HOME_ADVANTAGE),
+                learning_rate: params.learning_rate.unwrap_or(DEFAULT_LEARNING_RATE),
+                mean_learning_rate: params
+                    .mean_learning_rate
+                    .unwrap_or(DEFAULT_MEAN_LEARNING_RATE),
+                margin_sd: params.margin_sd.unwrap_or(DEFAULT_MARGIN_SD),
+            },
+        };
+        for team in params.teams {
+            model.add_team(None, &team);
+        }
+        model
+    }
+
+    fn team_stats(&self, team: &str) -> Result<&AttackDefenceTeamStats> {
+        self.model_stats
+            .get(team)
+            .ok_or(Error::UnknownTeam(team.to_string()))
+    }
+
+    pub fn predict_scores(&self, match_: &Match) -> Result<ScorePrediction> {
+        let home = self.team_stats(&match_.home_team)?;
+        let away = self.team_stats(&match_.away_team)?;
+        let params = &self.model_params;
+        Ok(ScorePrediction {
+            home_score: params.mean_score + home.attack - away.defence
+                + params.home_advantage / 2.0,
+            away_score: params.mean_score + away.attack
+                - home.defence
+                - params.home_advantage / 2.0,
+        })
+    }
+}
+
+impl RatingModel for AttackDefenceModel {
+    fn predict(&self, match_: &Match) -> Result<MatchPrediction> {
+        let scores = self.predict_scores(match_)?;
+        let home_team_win_prob = normal_cdf(scores.margin() / self.model_params.margin_sd);
+        Ok(MatchPrediction {
+            prediction: home_team_win_prob,
+            pred_margin: 0,
+            home_team_win: home_team_win_prob >= 0.5,
+            home_score: Some(scores.home_score),
+            away_score: Some(scores.away_score),
+            margin_distribution: Some(MarginDistribution::new(
+                scores.margin(),
+                self.model_params.margin_sd,
+            )),
+        })
+    }
+
+    /// Each side's points above or below expectation move its attack and the opponent's defence
+    fn update(&mut self, match_: &Match, match_result: &MatchResult) -> Result<()> {
+        let expected = self.predict_scores(match_)?;
+        let home_error = match_result.home_points as f64 - expected.home_score;
+        let away_error = match_result.away_points as f64 - expected.away_score;
+        let lr = self.model_params.learning_rate;
+
+        let home = self
+            .model_stats
+            .get_mut(&match_.home_team)
+            .ok_or_else(|| Error::UnknownTeam(match_.home_team.clone()))?;
+        home.attack += lr * home_error;
+        home.defence -= lr * away_error;
+
+        let away = self
+            .model_stats
+            .get_mut(&match_.away_team)
+            .ok_or_else(|| Error::UnknownTeam(match_.away_team.clone()))?;
+        away.attack += lr * away_error;
+        away.defence -= lr * home_error;
+
+        self.model_params.mean_score +=
+            self.model_params.mean_learning_rate * (home_error + away_error) / 2.0;
+        Ok(())
+    }
+
+    /// Expected points margin against an average team at a neutral venue
+    fn team_ratings(&self) -> Vec<TeamRating> {
+        self.model_stats
+            .iter()
+            .map(|(team, stats)| TeamRating {
+                team: team.clone(),
+                rating: stats.attack + stats.defence,
+                rd: None,
+                volatility: None,
+            })
+            .collect()
+    }
+
+    fn add_team(&mut self, _id: Option<TeamId>, team: &str) {
+        if self.model_stats.contains_key(team) {
+            return;
+        }
+        self.model_params.teams.insert(team.to_string());
+        self.model_stats.insert(
+            team.to_string(),
+            AttackDefenceTeamStats {
+                attack: 0.0,
+                defence: 0.0,
+            },
+        );
+    }
+
+    fn new_season(&mut self, regression: &SeasonRegression) {
+        for stats in self.model_stats.values_mut() {
+            stats.attack -= stats.attack * regression.rating_shrink;
+            stats.defence -= stats.defence * regression.rating_shrink;
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        snapshot::to_json(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::{fixture, Team};
+
+    const TOLERANCE: f64 = 0.001;
+
+    #[test]
+    fn test_attack_defence_model() {
+        let mut model = AttackDefenceModel::new(AttackDefenceModelInitParams {
+            teams: HashSet::from(["A".to_string(), "B".to_string()]),
+            mean_score: Some(80.0),
+            home_advantage: Some(0.0),
+            learning_rate: Some(0.1),
+            ..Default::default()
+        });
+        let match_ = fixture("A", "B");
+        let result = MatchResult {
+            winning_team: Some(Team {
+                name: "A".to_string(),
+            }),
+            winning_margin: Some(30),
+            draw: false,
+            home_team_won: true,
+            away_team_won: false,
+            home_points: 120,
+            away_points: 90,
+            home_score: None,
+            away_score: None,
+        };
+        model.update(&match_, &result).unwrap();
+
+        assert!((model.model_stats["A"].attack - 4.0).abs() < TOLERANCE);
+        assert!((model.model_stats["A"].defence + 1.0).abs() < TOLERANCE);
+        assert!((model.model_stats["B"].defence + 4.0).abs() < TOLERANCE);
+        let scores = model.predict_scores(&match_).unwrap();
+        assert!((scores.home_score - 88.125).abs() < TOLERANCE);
+        assert!((scores.away_score - 82.125).abs() < TOLERANCE);
+        assert!((scores.total() - 170.25).abs() < TOLERANCE);
+        assert!(model.predict(&match_).unwrap().home_team_win);
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tipping::models::distribution::{logit, sigmoid};
+
+/// Fewest past predictions worth fitting to; with fewer the calibration is left as the identity
+const MIN_FIT_GAMES: usize = 50;
+/// Ridge penalty pulling the fit towards the identity, which keeps it stable on small samples
+const RIDGE: f64 = 1.0;
+const MAX_ITERATIONS: usize = 50;
+
+/// Platt scaling of a model's home win probability: `sigmoid(slope * logit(p) + intercept)`.
+/// A slope above 1 stretches under-confident predictions, and the intercept corrects a
+/// bias towards home or away teams.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    pub slope: f64,
+    pub intercept: f64,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Calibration::identity()
+    }
+}
+
+impl Calibration {
+    /// Leaves probabilities unchanged
+    pub fn identity() -> Calibration {
+        Calibration {
+            slope: 1.0,
+            intercept: 0.0,
+        }
+    }
+
+    /// Fit by logistic regression of results on the log-odds of past predictions. Each
+    /// sample is the predicted home win probability and the home team's outcome (1 for a
+    /// win, 0.5 for a draw, 0 for a loss).
+    pub fn fit(samples: &[(f64, f64)]) -> Calibration {
+        if samples.len() < MIN_FIT_GAMES {
+            return Calibration::identity();
+        }
+        let mut calibration = Calibration::identity();
+        for _ in 0..MAX_ITERATIONS {
+            // Newton step on the penalised log loss
+            let (mut g_slope, mut g_intercept) = (
+                RIDGE * (calibration.slope - 1.0),
+                RIDGE * calibration.intercept,
+            );
+            let (mut h_ss, mut h_si, mut h_ii) = (RIDGE, 0.0, RIDGE);
+            for (prob, outcome) in samples {
+                let x = logit(*prob);
+                let q = sigmoid(calibration.slope * x + calibration.intercept);
+                let w = q * (1.0 - q);
+                g_slope += (q - outcome) * x;
+                g_intercept += q - outcome;
+                h_ss += w * x * x;
+                h_si += w * x;
+                h_ii += w;
+            }
+            let det = h_ss * h_ii - h_si * h_si;
+            let step_slope = (h_ii * g_slope - h_si * g_intercept) / det;
+            let step_intercept = (h_ss * g_intercept - h_si * g_slope) / det;
+            calibration.slope -= step_slope;
+            calibration.intercept -= step_intercept;
+            if step_slope.abs() + step_intercept.abs() < 1e-9 {
+                break;
+            }
+        }
+        calibration
+    }
+
+    pub fn calibrate(&self, prob: f64) -> f64 {
+        sigmoid(self.slope * logit(prob) + self.intercept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 0.01;
+
+    #[test]
+    fn test_calibration() {
+        let probs: Vec<f64> = (1..100).map(|x| 0.2 + 0.6 * x as f64 / 100.0).collect();
+        let few: Vec<(f64, f64)> = probs.iter().take(10).map(|p| (*p, 1.0)).collect();
+        assert_eq!(Calibration::fit(&few), Calibration::identity());
+
+        // Outcomes twice as extreme in log-odds as the predictions, with a home lean
+        let mut samples = vec![];
+        for _ in 0..20 {
+            samples.extend(probs.iter().map(|p| (*p, sigmoid(2.0 * logit(*p) + 0.3))));
+        }
+        let calibration = Calibration::fit(&samples);
+        assert!((calibration.slope - 2.0).abs() < TOLERANCE);
+        assert!((calibration.intercept - 0.3).abs() < TOLERANCE);
+        assert!(calibration.calibrate(0.6) > 0.6);
+        assert!((Calibration::identity().calibrate(0.6) - 0.6).abs() < 1e-9);
+        assert!((Calibration::identity().calibrate(0.995) - 0.995).abs() < 1e-9);
+        assert!(Calibration::identity().calibrate(1.0).is_finite());
+    }
+}
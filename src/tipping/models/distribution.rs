@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Standard normal cumulative distribution function
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / 2f64.sqrt()))
+}
+
+/// Abramowitz and Stegun approximation 7.1.26, accurate to about 1.5e-7
+pub fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x >= 0.0 {
+        y
+    } else {
+        -y
+    }
+}
+
+/// Standard normal density
+pub fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * PI).sqrt()
+}
+
+/// Inverse of the standard normal cdf, using Acklam's rational approximation
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.024_25;
+
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Log-odds of `p`, kept finite for probabilities of exactly 0 or 1
+pub fn logit(p: f64) -> f64 {
+    let p = p.clamp(f64::EPSILON, 1.0 - f64::EPSILON);
+    (p / (1.0 - p)).ln()
+}
+
+pub fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Widest margin given its own probability by `MarginDistribution::pmf`
+pub const PMF_LIMIT: i32 = 150;
+
+/// Normal distribution of the home team's margin, negative when the away team wins
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MarginDistribution {
+    pub mean: f64,
+    pub sd: f64,
+}
+
+impl MarginDistribution {
+    pub fn new(mean: f64, sd: f64) -> MarginDistribution {
+        MarginDistribution { mean, sd }
+    }
+
+    /// Probability the home team's margin is at most `margin`
+    pub fn cdf(&self, margin: f64) -> f64 {
+        normal_cdf((margin - self.mean) / self.sd)
+    }
+
+    /// Probability the home team wins by more than `margin` points
+    pub fn prob_home_by_more_than(&self, margin: f64) -> f64 {
+        1.0 - self.cdf(margin)
+    }
+
+    /// Probability the away team wins by more than `margin` points
+    pub fn prob_away_by_more_than(&self, margin: f64) -> f64 {
+        self.cdf(-margin)
+    }
+
+    /// The same spread, recentred so the home team wins with probability `home_prob`
+    pub fn with_home_probability(&self, home_prob: f64) -> MarginDistribution {
+        MarginDistribution::new(self.sd * normal_quantile(home_prob), self.sd)
+    }
+
+    /// Home margin below which a fraction `p` of outcomes fall
+    pub fn quantile(&self, p: f64) -> f64 {
+        self.mean + self.sd * normal_quantile(p)
+    }
+
+    /// Probability of each whole-point home margin from -`PMF_LIMIT` to `PMF_LIMIT`,
+    /// with the tails beyond folded into the end points
+    pub fn pmf(&self) -> Vec<(i32, f64)> {
+        (-PMF_LIMIT..=PMF_LIMIT)
+            .map(|margin| {
+                let upper = if margin == PMF_LIMIT {
+                    1.0
+                } else {
+                    self.cdf(margin as f64 + 0.5)
+                };
+                let lower = if margin == -PMF_LIMIT {
+                    0.0
+                } else {
+                    self.cdf(margin as f64 - 0.5)
+                };
+                (margin, upper - lower)
+            })
+            .collect()
+    }
+
+    /// Continuous ranked probability score against the actual home margin (lower is better)
+    pub fn crps(&self, actual: f64) -> f64 {
+        let z = (actual - self.mean) / self.sd;
+        self.sd * (z * (2.0 * normal_cdf(z) - 1.0) + 2.0 * normal_pdf(z) - 1.0 / PI.sqrt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 0.000_001;
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < TOLERANCE);
+        assert!((normal_cdf(1.0) - 0.841_345).abs() < TOLERANCE);
+        assert!((normal_cdf(-1.96) - 0.024_998).abs() < TOLERANCE);
+        assert!((normal_quantile(0.975) - 1.959_964).abs() < TOLERANCE);
+        assert!((normal_quantile(0.01) + 2.326_348).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_margin_distribution() {
+        let dist = MarginDistribution::new(12.0, 36.0);
+
+        assert!((dist.quantile(0.5) - 12.0).abs() < TOLERANCE);
+        assert!((dist.prob_home_by_more_than(48.0) - (1.0 - normal_cdf(1.0))).abs() < TOLERANCE);
+        assert!((dist.prob_away_by_more_than(24.0) - normal_cdf(-1.0)).abs() < TOLERANCE);
+
+        let pmf = dist.pmf();
+        assert_eq!(pmf.len(), 301);
+        assert!((pmf.iter().map(|x| x.1).sum::<f64>() - 1.0).abs() < TOLERANCE);
+
+        // A perfect forecast of the mean scores sd * (sqrt(2) - 1) / sqrt(pi)
+        let expected = 36.0 * (2f64.sqrt() - 1.0) / PI.sqrt();
+        assert!((dist.crps(12.0) - expected).abs() < TOLERANCE);
+        assert!(dist.crps(80.0) > dist.crps(20.0));
+
+        let recentred = dist.with_home_probability(0.3);
+        assert!((recentred.prob_home_by_more_than(0.0) - 0.3).abs() < TOLERANCE);
+        assert_eq!(recentred.sd, 36.0);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    error::{Error, Result},
+    tipping::{
+        models::{snapshot, RatingModel, SeasonRegression, TeamRating},
+        teams::TeamId,
+        Match, MatchPrediction, MatchResult,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EloModel {
+    pub ratings: HashMap<String, f64>,
+    pub model_params: EloModelParams,
+}
+
+#[derive(Debug, Default)]
+pub struct EloModelInitParams {
+    pub teams: HashSet<String>,
+    pub starting_elo: Option<f64>,
+    pub k_factor: Option<f64>,
+    /// Home advantage in rating points for teams without their own offset
+    pub home_advantage: Option<f64>,
+    /// Per-team home advantage, overriding `home_advantage`
+    pub offsets: Option<HashMap<String, f64>>,
+    /// Scale each update by the size of the winning margin
+    pub margin_multiplier: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EloModelParams {
+    pub teams: HashSet<String>,
+    pub starting_elo: f64,
+    pub k_factor: f64,
+    pub home_advantage: f64,
+    pub offsets: HashMap<String, f64>,
+    pub margin_multiplier: bool,
+}
+
+impl EloModel {
+    pub fn new(params: EloModelInitParams) -> EloModel {
+        const STARTING_ELO: f64 = 1500.0;
+        const DEFAULT_K_FACTOR: f64 = 40.0;
+        const DEFAULT_HOME_ADVANTAGE: f64 = 35.0;
+
+        let starting_elo = params.starting_elo.unwrap_or(STARTING_ELO);
+        let ratings = params
+            .teams
+            .iter()
+            .map(|team| (team.clone(), starting_elo))
+            .collect();
+
+        EloModel {
+            ratings,
+            model_params: EloModelParams {
+                teams: params.teams,
+                starting_elo,
+                k_factor: params.k_factor.unwrap_or(DEFAULT_K_FACTOR),
+                home_advantage: params.home_advantage.unwrap_or(DEFAULT_HOME_ADVANTAGE),
+                offsets: params.offsets.unwrap_or_default(),
+                margin_multiplier: params.margin_multiplier.unwrap_or(false),
+            },
+        }
+    }
+
+    fn rating(&self, team: &str) -> Result<f64> {
+        self.ratings
+            .get(team)
+            .copied()
+            .ok_or(Error::UnknownTeam(team.to_string()))
+    }
+
+    fn home_advantage(&self, team: &str) -> f64 {
+        *self
+            .model_params
+            .offsets
+            .get(team)
+            .unwrap_or(&self.model_params.home_advantage)
+    }
+
+    /// Home team rating (including home advantage) minus away team rating
+    fn rating_difference(&self, match_: &Match) -> Result<f64> {
+        Ok(
+            self.rating(&match_.home_team)? + self.home_advantage(&match_.home_team)
+                - self.rating(&match_.away_team)?,
+        )
+    }
+}
+
+fn expected_score(rating_difference: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-rating_difference / 400.0))
+}
+
+/// Margin of victory multiplier, damped when the favourite wins so ratings do not run away
+fn margin_multiplier(match_result: &MatchResult, rating_difference: f64) -> f64 {
+    if match_result.draw {
+        return 1.0;
+    }
+    let winner_difference = if match_result.home_team_won {
+        rating_difference
+    } else {
+        -rating_difference
+    };
+    let margin = match_result.winning_margin.unwrap_or(0) as f64;
+    (margin + 1.0).ln() * 2.2 / (winner_difference * 0.001 + 2.2)
+}
+
+impl RatingModel for EloModel {
+    fn predict(&self, match_: &Match) -> Result<MatchPrediction> {
+        let home_team_win_prob = expected_score(self.rating_difference(match_)?);
+        Ok(MatchPrediction {
+            prediction: home_team_win_prob,
+            pred_margin: 0,
+            home_team_win: home_team_win_prob >= 0.5,
+            home_score: None,
+            away_score: None,
+            margin_distribution: None,
+        })
+    }
+
+    fn rating_difference(&self, match_: &Match) -> Result<f64> {
+        EloModel::rating_difference(self, match_)
+    }
+
+    fn update(&mut self, match_: &Match, match_result: &MatchResult) -> Result<()> {
+        let rating_difference = self.rating_difference(match_)?;
+        let outcome = if match_result.draw {
+            0.5
+        } else if match_result.home_team_won {
+            1.0
+        } else {
+            0.0
+        };
+        let multiplier = if self.model_params.margin_multiplier {
+            margin_multiplier(match_result, rating_difference)
+        } else {
+            1.0
+        };
+        let delta =
+            self.model_params.k_factor * multiplier * (outcome - expected_score(rating_difference));
+
+        *self
+            .ratings
+            .get_mut(&match_.home_team)
+            .ok_or_else(|| Error::UnknownTeam(match_.home_team.clone()))? += delta;
+        *self
+            .ratings
+            .get_mut(&match_.away_team)
+            .ok_or_else(|| Error::UnknownTeam(match_.away_team.clone()))? -= delta;
+        Ok(())
+    }
+
+    fn team_ratings(&self) -> Vec<TeamRating> {
+        self.ratings
+            .iter()
+            .map(|(team, rating)| TeamRating {
+                team: team.clone(),
+                rating: *rating,
+                rd: None,
+                volatility: None,
+            })
+            .collect()
+    }
+
+    fn add_team(&mut self, _id: Option<TeamId>, team: &str) {
+        if self.ratings.contains_key(team) {
+            return;
+        }
+        self.model_params.teams.insert(team.to_string());
+        self.ratings
+            .insert(team.to_string(), self.model_params.starting_elo);
+    }
+
+    fn new_season(&mut self, regression: &SeasonRegression) {
+        if self.ratings.is_empty() {
+            return;
+        }
+        let mean = self.ratings.values().sum::<f64>() / self.ratings.len() as f64;
+        for rating in self.ratings.values_mut() {
+            *rating -= (*rating - mean) * regression.rating_shrink;
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        snapshot::to_json(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::{fixture, Team};
+
+    const TOLERANCE: f64 = 0.001;
+
+    fn home_win(margin: u32) -> MatchResult {
+        MatchResult {
+            winning_team: Some(Team {
+                name: "A".to_string(),
+            }),
+            winning_margin: Some(margin),
+            draw: false,
+            home_team_won: true,
+            away_team_won: false,
+            home_points: 80 + margin,
+            away_points: 80,
+            home_score: None,
+            away_score: None,
+        }
+    }
+
+    #[test]
+    fn test_elo_model() {
+        let new_model = |margin_multiplier| {
+            EloModel::new(EloModelInitParams {
+                teams: HashSet::from(["A".to_string(), "B".to_string()]),
+                k_factor: Some(20.0),
+                home_advantage: Some(0.0),
+                margin_multiplier: Some(margin_multiplier),
+                ..Default::default()
+            })
+        };
+        let match_ = fixture("A", "B");
+
+        let mut model = new_model(false);
+        assert!((model.predict(&match_).unwrap().prediction - 0.5).abs() < TOLERANCE);
+        model.update(&match_, &home_win(30)).unwrap();
+        assert!((model.ratings["A"] - 1510.0).abs() < TOLERANCE);
+        assert!((model.ratings["B"] - 1490.0).abs() < TOLERANCE);
+        assert!(model.predict(&match_).unwrap().home_team_win);
+
+        model.new_season(&SeasonRegression {
+            rating_shrink: 0.5,
+            rd_inflation: 0.0,
+        });
+        assert!((model.ratings["A"] - 1505.0).abs() < TOLERANCE);
+
+        let mut close = new_model(true);
+        let mut thrashing = new_model(true);
+        close.update(&match_, &home_win(1)).unwrap();
+        thrashing.update(&match_, &home_win(100)).unwrap();
+        assert!(thrashing.ratings["A"] > close.ratings["A"]);
+    }
+}
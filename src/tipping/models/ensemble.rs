@@ -0,0 +1,226 @@
+use crate::{
+    error::Result,
+    tipping::{
+        models::{
+            distribution::{logit, sigmoid},
+            RatingModel, SeasonRegression, TeamRating,
+        },
+        teams::TeamId,
+        Match, MatchPrediction, MatchResult,
+    },
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// How member probabilities are combined into the ensemble probability
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Combination {
+    /// Weighted mean of the member probabilities
+    Mean,
+    /// Weighted mean of the member log-odds
+    LogOdds,
+    /// Log-odds weights learned online by gradient descent on log loss
+    Stacked { learning_rate: f64 },
+}
+
+/// Combines the home win probabilities of several rating models
+pub struct EnsembleModel {
+    pub members: Vec<Box<dyn RatingModel>>,
+    pub weights: Vec<f64>,
+    pub combination: Combination,
+}
+
+impl EnsembleModel {
+    /// Build an ensemble with equal weights on every member
+    pub fn new(members: Vec<Box<dyn RatingModel>>, combination: Combination) -> EnsembleModel {
+        let weights = vec![1.0 / members.len() as f64; members.len()];
+        EnsembleModel {
+            members,
+            weights,
+            combination,
+        }
+    }
+
+    fn member_predictions(&self, match_: &Match) -> Result<Vec<f64>> {
+        self.members
+            .iter()
+            .map(|x| Ok(x.predict(match_)?.prediction))
+            .collect()
+    }
+
+    /// Mean predicted (home, away) score over the members that predict scores
+    fn member_scores(&self, match_: &Match) -> Result<Option<(f64, f64)>> {
+        let mut scores = vec![];
+        for member in &self.members {
+            let p = member.predict(match_)?;
+            if let (Some(home), Some(away)) = (p.home_score, p.away_score) {
+                scores.push((home, away));
+            }
+        }
+        if scores.is_empty() {
+            return Ok(None);
+        }
+        let n = scores.len() as f64;
+        Ok(Some((
+            scores.iter().map(|x| x.0).sum::<f64>() / n,
+            scores.iter().map(|x| x.1).sum::<f64>() / n,
+        )))
+    }
+
+    fn combine(&self, probs: &[f64]) -> f64 {
+        match self.combination {
+            Combination::Mean => probs.iter().zip(&self.weights).map(|(p, w)| p * w).sum(),
+            Combination::LogOdds | Combination::Stacked { .. } => sigmoid(
+                probs
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|(p, w)| logit(*p) * w)
+                    .sum(),
+            ),
+        }
+    }
+}
+
+impl RatingModel for EnsembleModel {
+    fn predict(&self, match_: &Match) -> Result<MatchPrediction> {
+        let home_team_win_prob = self.combine(&self.member_predictions(match_)?);
+        let scores = self.member_scores(match_)?;
+        Ok(MatchPrediction {
+            prediction: home_team_win_prob,
+            pred_margin: 0,
+            home_team_win: home_team_win_prob >= 0.5,
+            home_score: scores.map(|x| x.0),
+            away_score: scores.map(|x| x.1),
+            margin_distribution: None,
+        })
+    }
+
+    fn update(&mut self, match_: &Match, match_result: &MatchResult) -> Result<()> {
+        if let Combination::Stacked { learning_rate } = self.combination {
+            // Fit the weights on the members' pre-match predictions
+            let probs = self.member_predictions(match_)?;
+            let error = self.combine(&probs) - match_result.home_outcome();
+            for (w, p) in self.weights.iter_mut().zip(&probs) {
+                *w -= learning_rate * error * logit(*p);
+            }
+        }
+        for member in self.members.iter_mut() {
+            member.update(match_, match_result)?;
+        }
+        Ok(())
+    }
+
+    /// Mean of the members' ratings for each team
+    fn team_ratings(&self) -> Vec<TeamRating> {
+        let mut totals: HashMap<String, (f64, usize)> = HashMap::new();
+        for rating in self.members.iter().flat_map(|x| x.team_ratings()) {
+            let total = totals.entry(rating.team).or_insert((0.0, 0));
+            total.0 += rating.rating;
+            total.1 += 1;
+        }
+        totals
+            .into_iter()
+            .map(|(team, (total, count))| TeamRating {
+                team,
+                rating: total / count as f64,
+                rd: None,
+                volatility: None,
+            })
+            .collect()
+    }
+
+    fn add_team(&mut self, id: Option<TeamId>, team: &str) {
+        for member in self.members.iter_mut() {
+            member.add_team(id, team);
+        }
+    }
+
+    fn new_season(&mut self, regression: &SeasonRegression) {
+        for member in self.members.iter_mut() {
+            member.new_season(regression);
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        let members = self
+            .members
+            .iter()
+            .map(|x| Ok(serde_json::from_str(&x.to_json()?)?))
+            .collect::<Result<Vec<serde_json::Value>>>()?;
+        let value = json!({
+            "combination": self.combination,
+            "weights": self.weights,
+            "members": members,
+        });
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::{
+        fixture,
+        models::{
+            elo::{EloModel, EloModelInitParams},
+            glicko::{GlickoModel, GlickoModelInitParams},
+        },
+        Team,
+    };
+    use std::collections::HashSet;
+
+    const TOLERANCE: f64 = 0.001;
+
+    fn members() -> Vec<Box<dyn RatingModel>> {
+        let teams = HashSet::from(["A".to_string(), "B".to_string()]);
+        vec![
+            Box::new(
+                GlickoModel::new(GlickoModelInitParams {
+                    teams: teams.clone(),
+                    ..Default::default()
+                })
+                .unwrap(),
+            ),
+            Box::new(EloModel::new(EloModelInitParams {
+                teams,
+                ..Default::default()
+            })),
+        ]
+    }
+
+    #[test]
+    fn test_ensemble() {
+        let match_ = fixture("A", "B");
+        let result = MatchResult {
+            winning_team: Some(Team {
+                name: "A".to_string(),
+            }),
+            winning_margin: Some(20),
+            draw: false,
+            home_team_won: true,
+            away_team_won: false,
+            home_points: 100,
+            away_points: 80,
+            home_score: None,
+            away_score: None,
+        };
+
+        let mean = EnsembleModel::new(members(), Combination::Mean);
+        let probs = mean.member_predictions(&match_).unwrap();
+        let expected = (probs[0] + probs[1]) / 2.0;
+        assert!((mean.predict(&match_).unwrap().prediction - expected).abs() < TOLERANCE);
+
+        let log_odds = EnsembleModel::new(members(), Combination::LogOdds);
+        let expected = sigmoid((logit(probs[0]) + logit(probs[1])) / 2.0);
+        assert!((log_odds.predict(&match_).unwrap().prediction - expected).abs() < TOLERANCE);
+
+        let mut stacked =
+            EnsembleModel::new(members(), Combination::Stacked { learning_rate: 0.1 });
+        stacked.update(&match_, &result).unwrap();
+        // Glicko has no home advantage here so is uninformative; Elo tipped the winner
+        assert!((stacked.weights[0] - 0.5).abs() < TOLERANCE);
+        assert!(stacked.weights[1] > 0.5);
+        assert!(stacked.to_json().unwrap().contains("\"members\""));
+    }
+}
@@ -1,47 +1,131 @@
-use crate::tipping::{Match, MatchPrediction, MatchResult};
+use crate::{
+    error::{Error, Result},
+    tipping::{
+        models::{snapshot, RatingModel, SeasonRegression, TeamRating},
+        teams::{TeamId, TeamRegistry},
+        venues::{team_state, venue_state},
+        Match, MatchPrediction, MatchResult,
+    },
+};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
     f64::consts::PI,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlickoTeamStats {
     pub elo: f64,
     pub rd: f64,
     pub volatility: f64,
-    pub offset: f64,
+    #[serde(default)]
+    pub last_played: Option<chrono::NaiveDateTime>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlickoModel {
-    pub model_stats: HashMap<String, GlickoTeamStats>,
+    pub model_stats: HashMap<TeamId, GlickoTeamStats>,
     pub model_params: GlickoModelParams,
+    /// Resolves the names and Squiggle IDs in matches to the keys of `model_stats`
+    pub registry: TeamRegistry,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct GlickoModelInitParams {
     pub teams: HashSet<String>,
     pub starting_rd: Option<f64>,
     pub starting_volatility: Option<f64>,
+    /// Home advantage per team, keyed by any name the registry knows the team by
     pub offsets: Option<HashMap<String, f64>>,
     pub scale_factor: Option<f64>,
     pub volatility_constraint: Option<f64>,
     pub starting_elo: Option<f64>,
+    /// Length of a Glicko rating period; when set, rd grows with time between matches
+    pub rating_period_days: Option<f64>,
+    pub home_advantage: Option<HomeAdvantage>,
+    /// Home advantage per team, then per venue, used by `HomeAdvantage::Venue`
+    pub venue_offsets: Option<HashMap<String, HashMap<String, f64>>>,
+    pub outcome: Option<OutcomeFunction>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlickoModelParams {
-    pub teams: HashSet<String>,
+    pub teams: HashSet<TeamId>,
     pub starting_rd: f64,
     pub starting_volatility: f64,
-    pub offsets: HashMap<String, f64>,
+    pub offsets: HashMap<TeamId, f64>,
     pub scale_factor: f64,
     pub volatility_constraint: f64,
     pub starting_elo: f64,
+    #[serde(default)]
+    pub rating_period_days: Option<f64>,
+    #[serde(default)]
+    pub home_advantage: HomeAdvantage,
+    #[serde(default)]
+    pub venue_offsets: HashMap<TeamId, HashMap<String, f64>>,
+    #[serde(default)]
+    pub outcome: OutcomeFunction,
+}
+
+/// Converts a match result into the home team's score for the Glicko update
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum OutcomeFunction {
+    /// 1.0 for a home win, 0.5 for a draw and 0.0 for a loss
+    #[default]
+    WinLoss,
+    /// Logistic transform of the home team's margin, `1 / (1 + exp(-margin / scale))`
+    MarginLogistic { scale: f64 },
+}
+
+impl OutcomeFunction {
+    pub fn score(&self, match_result: &MatchResult) -> f64 {
+        match self {
+            OutcomeFunction::WinLoss => {
+                if match_result.draw {
+                    0.5
+                } else if match_result.home_team_won {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            OutcomeFunction::MarginLogistic { scale } => {
+                1.0 / (1.0 + (-match_result.home_margin() as f64 / scale).exp())
+            }
+        }
+    }
+
+    /// Fails with `Error::InvalidParameter` if this is a margin logistic whose scale is
+    /// not positive
+    pub fn validate(&self) -> Result<()> {
+        if let OutcomeFunction::MarginLogistic { scale } = self {
+            if scale.is_nan() || *scale <= 0.0 {
+                return Err(Error::InvalidParameter(format!(
+                    "margin logistic scale must be positive, got {}",
+                    scale
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How the home team's offset is applied to a match
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HomeAdvantage {
+    /// The home team's offset applies whenever it is listed as the home team
+    #[default]
+    Team,
+    /// The offset applies only at venues in the home team's state against interstate opposition
+    HomeState,
+    /// Offsets are looked up per team and venue, falling back to `HomeState`
+    Venue,
 }
 
 impl GlickoModel {
-    pub fn new(params: GlickoModelInitParams) -> GlickoModel {
+    /// Fails with `Error::InvalidParameter` if `outcome` is a margin logistic whose scale
+    /// is not positive
+    pub fn new(params: GlickoModelInitParams) -> Result<GlickoModel> {
         const STARTING_ELO: f64 = 1500.0;
         const DEFAULT_STARTING_RD: f64 = 15.0;
         const DEFAULT_STARTING_VOLATILITY: f64 = 0.05;
@@ -52,71 +136,254 @@ impl GlickoModel {
         let starting_volatility: f64 = params
             .starting_volatility
             .unwrap_or(DEFAULT_STARTING_VOLATILITY);
-        let offsets: HashMap<String, f64> = params.offsets.unwrap_or_default();
         let scale_factor: f64 = params.scale_factor.unwrap_or(DEFAULT_SCALE_FACTOR);
         let volatility_constraint: f64 = params
             .volatility_constraint
             .unwrap_or(VOLATILITY_CONSTRAINT);
         let starting_elo: f64 = params.starting_elo.unwrap_or(STARTING_ELO);
+        let outcome = params.outcome.unwrap_or_default();
+        outcome.validate()?;
+
+        let mut registry = TeamRegistry::afl();
+        let offsets: HashMap<TeamId, f64> = params
+            .offsets
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(team, offset)| (registry.resolve(None, &team), offset))
+            .collect();
+        let venue_offsets: HashMap<TeamId, HashMap<String, f64>> = params
+            .venue_offsets
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(team, venues)| (registry.resolve(None, &team), venues))
+            .collect();
 
         let mut model_stats = HashMap::new();
-        for team in params.teams.clone().into_iter() {
+        for team in params.teams.iter() {
+            let id = registry.resolve(None, team);
             let team_stats = GlickoTeamStats {
-                elo: STARTING_ELO,
+                elo: starting_elo,
                 rd: starting_rating_deviation,
                 volatility: starting_volatility,
-                offset: *offsets.get(&team).unwrap_or(&0.0),
+                last_played: None,
             };
-            model_stats.insert(team, team_stats);
+            model_stats.insert(id, team_stats);
         }
 
-        GlickoModel {
+        let teams = model_stats.keys().copied().collect();
+        Ok(GlickoModel {
             model_stats,
+            registry,
             model_params: GlickoModelParams {
-                teams: params.teams,
+                teams,
                 starting_volatility,
                 offsets,
                 scale_factor,
                 volatility_constraint,
                 starting_rd: starting_rating_deviation,
                 starting_elo,
+                rating_period_days: params.rating_period_days,
+                home_advantage: params.home_advantage.unwrap_or_default(),
+                venue_offsets,
+                outcome,
             },
-        }
+        })
+    }
+
+    /// Read a model written by `snapshot::save`, rejecting parameters `new` would refuse
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<GlickoModel> {
+        let model: GlickoModel = snapshot::load(path)?;
+        model.model_params.outcome.validate()?;
+        Ok(model)
+    }
+
+    /// Ratings of the team known by `team`, under any of its names
+    pub fn stats(&self, team: &str) -> Option<&GlickoTeamStats> {
+        self.registry
+            .id(team)
+            .and_then(|id| self.model_stats.get(&id))
+    }
+
+    pub fn stats_mut(&mut self, team: &str) -> Option<&mut GlickoTeamStats> {
+        self.registry
+            .id(team)
+            .and_then(|id| self.model_stats.get_mut(&id))
     }
 }
 
 // Display logic moved to presentation module
 
-pub fn predict(model: &GlickoModel, match_: &Match, scale: Option<f64>) -> MatchPrediction {
+impl RatingModel for GlickoModel {
+    fn predict(&self, match_: &Match) -> Result<MatchPrediction> {
+        predict(self, match_, None)
+    }
+
+    fn rating_difference(&self, match_: &Match) -> Result<f64> {
+        rating_difference(self, match_)
+    }
+
+    fn update(&mut self, match_: &Match, match_result: &MatchResult) -> Result<()> {
+        *self = update(self.clone(), match_, match_result)?;
+        Ok(())
+    }
+
+    fn team_ratings(&self) -> Vec<TeamRating> {
+        self.model_stats
+            .iter()
+            .map(|(id, stats)| TeamRating {
+                team: self.registry.name(*id).unwrap_or_default().to_string(),
+                rating: stats.elo,
+                rd: Some(stats.rd),
+                volatility: Some(stats.volatility),
+            })
+            .collect()
+    }
+
+    fn add_team(&mut self, id: Option<TeamId>, team: &str) {
+        let id = self.registry.resolve(id, team);
+        if self.model_stats.contains_key(&id) {
+            return;
+        }
+        let params = &mut self.model_params;
+        params.teams.insert(id);
+        self.model_stats.insert(
+            id,
+            GlickoTeamStats {
+                elo: params.starting_elo,
+                rd: params.starting_rd,
+                volatility: params.starting_volatility,
+                last_played: None,
+            },
+        );
+    }
+
+    fn new_season(&mut self, regression: &SeasonRegression) {
+        *self = regress(self.clone(), regression);
+    }
+
+    fn to_json(&self) -> Result<String> {
+        snapshot::to_json(self)
+    }
+}
+
+/// Regress every team toward the league mean ahead of a new season
+pub fn regress(mut model: GlickoModel, regression: &SeasonRegression) -> GlickoModel {
+    if model.model_stats.is_empty() {
+        return model;
+    }
+    let mean_elo =
+        model.model_stats.values().map(|x| x.elo).sum::<f64>() / model.model_stats.len() as f64;
+    let starting_rd = model.model_params.starting_rd;
+    for stats in model.model_stats.values_mut() {
+        stats.elo -= (stats.elo - mean_elo) * regression.rating_shrink;
+        stats.rd += (starting_rd - stats.rd) * regression.rd_inflation;
+    }
+    model
+}
+
+/// Grow a team's rd for the rating periods it sat idle since its last match
+fn inflate_rd(
+    params: &GlickoModelParams,
+    stats: &GlickoTeamStats,
+    date: chrono::NaiveDateTime,
+) -> f64 {
+    let (Some(period_days), Some(last_played)) = (params.rating_period_days, stats.last_played)
+    else {
+        return stats.rd;
+    };
+    let elapsed_days = (date - last_played).num_seconds() as f64 / 86_400.0;
+    // The update itself adds one period of volatility, so only count the periods beyond that
+    let idle_periods = (elapsed_days / period_days - 1.0).max(0.0);
+    let rd_scaled = stats.rd / params.scale_factor;
+    let inflated =
+        (rd_scaled.powi(2) + stats.volatility.powi(2) * idle_periods).sqrt() * params.scale_factor;
+    inflated.min(params.starting_rd.max(stats.rd))
+}
+
+/// Home advantage, in elo points, given to the home team for this match
+fn home_offset(model: &GlickoModel, match_: &Match, h_team: TeamId, a_team: TeamId) -> f64 {
+    let params = &model.model_params;
+    let team_offset = *params.offsets.get(&h_team).unwrap_or(&0.0);
+    let Some(venue) = &match_.venue else {
+        return team_offset;
+    };
+    if params.home_advantage == HomeAdvantage::Venue {
+        if let Some(offset) = params.venue_offsets.get(&h_team).and_then(|x| x.get(venue)) {
+            return *offset;
+        }
+    }
+    if params.home_advantage == HomeAdvantage::Team {
+        return team_offset;
+    }
+    // States are keyed by canonical name, so a renamed club keeps its home state
+    let state = |team| model.registry.name(team).and_then(team_state);
+    match (venue_state(venue), state(h_team), state(a_team)) {
+        (Some(venue), Some(home), Some(away)) if venue == home && venue != away => team_offset,
+        (Some(_), Some(_), Some(_)) => 0.0,
+        // Without a known venue and both clubs' states, fall back to the flat offset
+        _ => team_offset,
+    }
+}
+
+/// The home and away teams of a match, by Squiggle ID where the match has one
+fn match_teams(model: &GlickoModel, match_: &Match) -> Result<(TeamId, TeamId)> {
+    let find = |id, name: &String| {
+        model
+            .registry
+            .find(id, name)
+            .ok_or(Error::UnknownTeam(name.clone()))
+    };
+    Ok((
+        find(match_.home_team_id, &match_.home_team)?,
+        find(match_.away_team_id, &match_.away_team)?,
+    ))
+}
+
+fn team_stats(model: &GlickoModel, team: TeamId) -> Result<&GlickoTeamStats> {
+    model.model_stats.get(&team).ok_or(Error::UnknownTeam(
+        model.registry.name(team).unwrap_or_default().to_string(),
+    ))
+}
+
+pub fn predict(model: &GlickoModel, match_: &Match, scale: Option<f64>) -> Result<MatchPrediction> {
     let scale: f64 = scale.unwrap_or(2.0f64.sqrt());
-    let h_team = &match_.home_team;
-    let a_team = &match_.away_team;
+    let (h_team, a_team) = match_teams(model, match_)?;
 
-    let mu_h = (model.model_stats.get(h_team).unwrap().elo
-        + model.model_params.offsets.get(h_team).unwrap_or(&0.0)
+    let mu_h = (team_stats(model, h_team)?.elo + home_offset(model, match_, h_team, a_team)
         - model.model_params.starting_elo)
         / model.model_params.scale_factor;
-    let mu_a = (model.model_stats.get(a_team).unwrap().elo - model.model_params.starting_elo)
+    let mu_a = (team_stats(model, a_team)?.elo - model.model_params.starting_elo)
         / model.model_params.scale_factor;
-    let home_team_win_prob = (1.0 / (1.0 + (-scale * (mu_h - mu_a)).exp()))
-        .min(0.99)
-        .max(0.01);
-    MatchPrediction {
+    let home_team_win_prob = 1.0 / (1.0 + (-scale * (mu_h - mu_a)).exp());
+    Ok(MatchPrediction {
         prediction: home_team_win_prob,
         pred_margin: 0,
         home_team_win: home_team_win_prob >= 0.5,
-    }
+        home_score: None,
+        away_score: None,
+        margin_distribution: None,
+    })
 }
 
-pub fn update(mut model: GlickoModel, match_: &Match, match_result: &MatchResult) -> GlickoModel {
-    let h_team = &match_.home_team;
-    let a_team = &match_.away_team;
-    let mut h_team_stats = model.model_stats.clone().get(h_team).unwrap().clone();
-    let mut a_team_stats = model.model_stats.clone().get(a_team).unwrap().clone();
+/// Home team elo minus away team elo, excluding any home advantage
+pub fn rating_difference(model: &GlickoModel, match_: &Match) -> Result<f64> {
+    let (h_team, a_team) = match_teams(model, match_)?;
+    Ok(team_stats(model, h_team)?.elo - team_stats(model, a_team)?.elo)
+}
 
-    let mut h_team_rating = (h_team_stats.elo
-        + model.model_params.offsets.get(h_team).unwrap_or(&0.0)
-        - model.model_params.starting_elo)
+pub fn update(
+    mut model: GlickoModel,
+    match_: &Match,
+    match_result: &MatchResult,
+) -> Result<GlickoModel> {
+    let (h_team, a_team) = match_teams(&model, match_)?;
+    let mut h_team_stats = team_stats(&model, h_team)?.clone();
+    let mut a_team_stats = team_stats(&model, a_team)?.clone();
+    h_team_stats.rd = inflate_rd(&model.model_params, &h_team_stats, match_.date);
+    a_team_stats.rd = inflate_rd(&model.model_params, &a_team_stats, match_.date);
+
+    let offset = home_offset(&model, match_, h_team, a_team);
+    let mut h_team_rating = (h_team_stats.elo + offset - model.model_params.starting_elo)
         / model.model_params.scale_factor;
     let mut a_team_rating =
         (a_team_stats.elo - model.model_params.starting_elo) / model.model_params.scale_factor;
@@ -127,13 +394,7 @@ pub fn update(mut model: GlickoModel, match_: &Match, match_result: &MatchResult
     let v_h = v_(h_team_rating, a_team_rating, a_team_rd_scaled);
     let v_a = v_(a_team_rating, h_team_rating, h_team_rd_scaled);
 
-    let outcome = if match_result.draw {
-        0.5
-    } else if match_result.home_team_won {
-        1.0
-    } else {
-        0.0
-    };
+    let outcome = model.model_params.outcome.score(match_result);
 
     h_team_stats.volatility = new_volatility(
         &model.model_params,
@@ -171,23 +432,20 @@ pub fn update(mut model: GlickoModel, match_: &Match, match_result: &MatchResult
     h_team_rating += d1;
     a_team_rating += d2;
 
-    h_team_stats.elo = h_team_rating * model.model_params.scale_factor
-        + model.model_params.starting_elo
-        - h_team_stats.offset;
+    h_team_stats.elo =
+        h_team_rating * model.model_params.scale_factor + model.model_params.starting_elo - offset;
     a_team_stats.elo =
         a_team_rating * model.model_params.scale_factor + model.model_params.starting_elo;
 
     h_team_stats.rd = h_team_rd_scaled * model.model_params.scale_factor;
     a_team_stats.rd = a_team_rd_scaled * model.model_params.scale_factor;
 
-    model.model_stats.get_mut(h_team).unwrap().volatility = h_team_stats.volatility;
-    model.model_stats.get_mut(h_team).unwrap().rd = h_team_stats.rd;
-    model.model_stats.get_mut(h_team).unwrap().elo = h_team_stats.elo;
+    h_team_stats.last_played = Some(match_.date);
+    a_team_stats.last_played = Some(match_.date);
 
-    model.model_stats.get_mut(a_team).unwrap().volatility = a_team_stats.volatility;
-    model.model_stats.get_mut(a_team).unwrap().rd = a_team_stats.rd;
-    model.model_stats.get_mut(a_team).unwrap().elo = a_team_stats.elo;
-    model
+    model.model_stats.insert(h_team, h_team_stats);
+    model.model_stats.insert(a_team, a_team_stats);
+    Ok(model)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -270,7 +528,7 @@ fn delta_(score: f64, mu: f64, mu_j: f64, phi_j: f64) -> f64 {
 
 #[cfg(test)]
 mod tests {
-    use crate::tipping::Team;
+    use crate::tipping::{fixture, Team};
 
     use super::*;
 
@@ -286,13 +544,19 @@ mod tests {
             offsets: None,
             scale_factor: None,
             volatility_constraint: None,
+            rating_period_days: None,
+            home_advantage: None,
+            venue_offsets: None,
+            outcome: None,
         };
 
-        let mut model = GlickoModel::new(model_params);
+        let mut model = GlickoModel::new(model_params).unwrap();
         // Model display removed - use presentation module
         let match_ = Match {
             home_team: "A".to_string(),
             away_team: "B".to_string(),
+            home_team_id: None,
+            away_team_id: None,
             venue: None,
             date: chrono::NaiveDateTime::parse_from_str("2024-04-01 10:10:10", "%Y-%m-%d %H:%M:%S")
                 .unwrap(),
@@ -309,47 +573,27 @@ mod tests {
             draw: false,
             home_team_won: true,
             away_team_won: false,
+            home_points: 80,
+            away_points: 80,
+            home_score: None,
+            away_score: None,
         };
-        model = update(model.clone(), &match_, &match_result);
+        model = update(model.clone(), &match_, &match_result).unwrap();
         // Model display removed - use presentation module
-        assert!(
-            (model.model_stats.get(&h_team.name).unwrap().elo - 1500.8613081137828).abs()
-                < TOLERANCE
-        );
-        assert!(
-            (model.model_stats.get(&a_team.name).unwrap().elo - 1499.1386918862172).abs()
-                < TOLERANCE
-        );
-        model = update(model.clone(), &match_, &match_result);
+        assert!((model.stats(&h_team.name).unwrap().elo - 1500.8613081137828).abs() < TOLERANCE);
+        assert!((model.stats(&a_team.name).unwrap().elo - 1499.1386918862172).abs() < TOLERANCE);
+        model = update(model.clone(), &match_, &match_result).unwrap();
         // Model display removed - use presentation module
-        assert!(
-            (model.model_stats.get(&h_team.name).unwrap().elo - 1501.9303887754816).abs()
-                < TOLERANCE
-        );
-        assert!(
-            (model.model_stats.get(&a_team.name).unwrap().elo - 1498.0696112245184).abs()
-                < TOLERANCE
-        );
-        model = update(model.clone(), &match_, &match_result);
+        assert!((model.stats(&h_team.name).unwrap().elo - 1501.9303887754816).abs() < TOLERANCE);
+        assert!((model.stats(&a_team.name).unwrap().elo - 1498.0696112245184).abs() < TOLERANCE);
+        model = update(model.clone(), &match_, &match_result).unwrap();
         // Model display removed - use presentation module
-        assert!(
-            (model.model_stats.get(&h_team.name).unwrap().elo - 1503.2020226041004).abs()
-                < TOLERANCE
-        );
-        assert!(
-            (model.model_stats.get(&a_team.name).unwrap().elo - 1496.7979772958996).abs()
-                < TOLERANCE
-        );
-        model = update(model.clone(), &match_, &match_result);
+        assert!((model.stats(&h_team.name).unwrap().elo - 1503.2020226041004).abs() < TOLERANCE);
+        assert!((model.stats(&a_team.name).unwrap().elo - 1496.7979772958996).abs() < TOLERANCE);
+        model = update(model.clone(), &match_, &match_result).unwrap();
         // Model display removed - use presentation module
-        assert!(
-            (model.model_stats.get(&h_team.name).unwrap().elo - 1504.6700786004337).abs()
-                < TOLERANCE
-        );
-        assert!(
-            (model.model_stats.get(&a_team.name).unwrap().elo - 1495.3299213995663).abs()
-                < TOLERANCE
-        );
+        assert!((model.stats(&h_team.name).unwrap().elo - 1504.6700786004337).abs() < TOLERANCE);
+        assert!((model.stats(&a_team.name).unwrap().elo - 1495.3299213995663).abs() < TOLERANCE);
     }
 
     #[test]
@@ -362,13 +606,19 @@ mod tests {
             offsets: None,
             scale_factor: None,
             volatility_constraint: None,
+            rating_period_days: None,
+            home_advantage: None,
+            venue_offsets: None,
+            outcome: None,
         };
 
-        let mut model = GlickoModel::new(model_params);
+        let mut model = GlickoModel::new(model_params).unwrap();
         // Model display removed - use presentation module
         let match_ = Match {
             home_team: "A".to_string(),
             away_team: "B".to_string(),
+            home_team_id: None,
+            away_team_id: None,
             venue: None,
             date: chrono::NaiveDateTime::parse_from_str("2024-04-01 10:10:10", "%Y-%m-%d %H:%M:%S")
                 .unwrap(),
@@ -385,17 +635,268 @@ mod tests {
             draw: false,
             home_team_won: true,
             away_team_won: false,
+            home_points: 80,
+            away_points: 80,
+            home_score: None,
+            away_score: None,
         };
         for _ in 0..70 {
-            model = update(model.clone(), &match_, &match_result);
+            model = update(model.clone(), &match_, &match_result).unwrap();
         }
         // Model display removed - use presentation module
-        assert!(
-            (model.model_stats.get(&h_team.name).unwrap().elo - 1691.9490472591813).abs()
-                < TOLERANCE
+        assert!((model.stats(&h_team.name).unwrap().elo - 1691.9490472591813).abs() < TOLERANCE);
+        assert!((model.stats(&a_team.name).unwrap().elo - 1308.0509527408187).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_regress() {
+        let model_params = GlickoModelInitParams {
+            teams: HashSet::from(["A".to_string(), "B".to_string()]),
+            starting_rd: None,
+            starting_volatility: None,
+            starting_elo: None,
+            offsets: None,
+            scale_factor: None,
+            volatility_constraint: None,
+            rating_period_days: None,
+            home_advantage: None,
+            venue_offsets: None,
+            outcome: None,
+        };
+        let mut model = GlickoModel::new(model_params).unwrap();
+        model.stats_mut("A").unwrap().elo = 1600.0;
+        model.stats_mut("A").unwrap().rd = 5.0;
+        model.stats_mut("B").unwrap().elo = 1400.0;
+
+        let regression = SeasonRegression {
+            rating_shrink: 0.25,
+            rd_inflation: 0.5,
+        };
+        model = regress(model, &regression);
+
+        assert!((model.stats("A").unwrap().elo - 1575.0).abs() < TOLERANCE);
+        assert!((model.stats("B").unwrap().elo - 1425.0).abs() < TOLERANCE);
+        assert!((model.stats("A").unwrap().rd - 10.0).abs() < TOLERANCE);
+        assert!((model.stats("B").unwrap().rd - 15.0).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_starting_elo() {
+        let mut model = GlickoModel::new(GlickoModelInitParams {
+            teams: HashSet::from(["A".to_string()]),
+            starting_elo: Some(1000.0),
+            ..Default::default()
+        })
+        .unwrap();
+        model.add_team(None, "B");
+
+        assert_eq!(model.stats("A").unwrap().elo, 1000.0);
+        assert_eq!(model.stats("B").unwrap().elo, 1000.0);
+    }
+
+    #[test]
+    fn test_rd_grows_between_matches() {
+        let mut models = [None, Some(7.0)].map(|rating_period_days| {
+            GlickoModel::new(GlickoModelInitParams {
+                teams: HashSet::from(["A".to_string(), "B".to_string()]),
+                starting_rd: Some(100.0),
+                starting_volatility: None,
+                starting_elo: None,
+                offsets: None,
+                scale_factor: None,
+                volatility_constraint: None,
+                rating_period_days,
+                home_advantage: None,
+                venue_offsets: None,
+                outcome: None,
+            })
+            .unwrap()
+        });
+        let date = |d: &str| chrono::NaiveDateTime::parse_from_str(d, "%Y-%m-%d %H:%M:%S").unwrap();
+        let result = MatchResult {
+            winning_team: None,
+            winning_margin: None,
+            draw: true,
+            home_team_won: false,
+            away_team_won: false,
+            home_points: 80,
+            away_points: 80,
+            home_score: None,
+            away_score: None,
+        };
+        for (i, day) in ["2024-04-01 10:10:10", "2024-04-08 10:10:10"]
+            .iter()
+            .enumerate()
+        {
+            let match_ = Match {
+                date: date(day),
+                ..fixture("A", "B")
+            };
+            models = models.map(|model| update(model, &match_, &result).unwrap());
+            if i == 1 {
+                // A weekly fixture adds no idle periods
+                assert!(
+                    (models[0].stats("A").unwrap().rd - models[1].stats("A").unwrap().rd).abs()
+                        < TOLERANCE
+                );
+            }
+        }
+        let rd_after_week = models[1].stats("A").unwrap().rd;
+
+        let late_match = Match {
+            date: date("2024-06-03 10:10:10"),
+            ..fixture("A", "B")
+        };
+        let inflated = inflate_rd(
+            &models[1].model_params,
+            models[1].stats("A").unwrap(),
+            late_match.date,
+        );
+        let unchanged = inflate_rd(
+            &models[0].model_params,
+            models[0].stats("A").unwrap(),
+            late_match.date,
         );
+        assert!(inflated > rd_after_week);
+        assert!(inflated <= 100.0);
+        assert!((unchanged - models[0].stats("A").unwrap().rd).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_home_state_advantage() {
+        let model = GlickoModel::new(GlickoModelInitParams {
+            teams: HashSet::from(["West Coast".to_string(), "Richmond".to_string()]),
+            starting_rd: None,
+            starting_volatility: None,
+            starting_elo: None,
+            offsets: Some(HashMap::from([("West Coast".to_string(), 20.0)])),
+            scale_factor: None,
+            volatility_constraint: None,
+            rating_period_days: None,
+            home_advantage: Some(HomeAdvantage::HomeState),
+            venue_offsets: None,
+            outcome: None,
+        })
+        .unwrap();
+        let at_venue = |venue: Option<&str>| Match {
+            venue: venue.map(|x| x.to_string()),
+            ..fixture("West Coast", "Richmond")
+        };
+
+        let at_home = predict(&model, &at_venue(Some("Perth Stadium")), None).unwrap();
+        let at_mcg = predict(&model, &at_venue(Some("M.C.G.")), None).unwrap();
+        let unknown = predict(&model, &at_venue(None), None).unwrap();
+
+        assert!(at_home.prediction > 0.5);
+        assert!((at_mcg.prediction - 0.5).abs() < TOLERANCE);
+        assert!((unknown.prediction - at_home.prediction).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_team_ids() {
+        let mut model = GlickoModel::new(GlickoModelInitParams {
+            teams: HashSet::from(["Greater Western Sydney".to_string(), "Sydney".to_string()]),
+            offsets: Some(HashMap::from([("GWS".to_string(), 20.0)])),
+            ..Default::default()
+        })
+        .unwrap();
+        let renamed = fixture("GWS Giants", "Sydney Swans");
+        let by_id = Match {
+            home_team: "Giants".to_string(),
+            home_team_id: Some(TeamId(9)),
+            away_team_id: Some(TeamId(16)),
+            ..renamed
+        };
+
+        assert_eq!(model.model_params.offsets[&TeamId(9)], 20.0);
+        let prediction = predict(&model, &by_id, None).unwrap();
+        assert!(prediction.prediction > 0.5);
+        assert!(predict(
+            &model,
+            &Match {
+                home_team_id: None,
+                ..by_id
+            },
+            None
+        )
+        .is_err());
+
+        model.add_team(None, "Greater Western Sydney Giants");
+        assert_eq!(model.model_stats.len(), 2);
+        assert!(model
+            .team_ratings()
+            .iter()
+            .any(|x| x.team == "Greater Western Sydney"));
+    }
+
+    #[test]
+    fn test_margin_outcome() {
+        let new_model = |outcome| {
+            GlickoModel::new(GlickoModelInitParams {
+                teams: HashSet::from(["A".to_string(), "B".to_string()]),
+                starting_rd: None,
+                starting_volatility: None,
+                starting_elo: None,
+                offsets: None,
+                scale_factor: None,
+                volatility_constraint: None,
+                rating_period_days: None,
+                home_advantage: None,
+                venue_offsets: None,
+                outcome: Some(outcome),
+            })
+            .unwrap()
+        };
+        let match_ = fixture("A", "B");
+        let win_by = |margin| MatchResult {
+            winning_team: Some(Team {
+                name: "A".to_string(),
+            }),
+            winning_margin: Some(margin),
+            draw: false,
+            home_team_won: true,
+            away_team_won: false,
+            home_points: 80 + margin,
+            away_points: 80,
+            home_score: None,
+            away_score: None,
+        };
+        let elo_after = |outcome, margin| {
+            update(new_model(outcome), &match_, &win_by(margin))
+                .unwrap()
+                .stats("A")
+                .unwrap()
+                .elo
+        };
+
+        for scale in [0.0, -30.0, f64::NAN] {
+            let params = GlickoModelInitParams {
+                outcome: Some(OutcomeFunction::MarginLogistic { scale }),
+                ..Default::default()
+            };
+            assert!(matches!(
+                GlickoModel::new(params),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+
+        // A snapshot edited to hold a bad scale is refused in the same way
+        let mut model = new_model(OutcomeFunction::WinLoss);
+        model.model_params.outcome = OutcomeFunction::MarginLogistic { scale: -30.0 };
+        let path = std::env::temp_dir().join("afl_glicko_outcome_test.json");
+        snapshot::save(&model, &path).unwrap();
+        assert!(matches!(
+            GlickoModel::load(&path),
+            Err(Error::InvalidParameter(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+
+        let logistic = OutcomeFunction::MarginLogistic { scale: 30.0 };
+        assert!(elo_after(logistic, 100) > elo_after(logistic, 1));
+        assert!(elo_after(logistic, 1) > 1500.0);
         assert!(
-            (model.model_stats.get(&a_team.name).unwrap().elo - 1308.0509527408187).abs()
+            (elo_after(OutcomeFunction::WinLoss, 100) - elo_after(OutcomeFunction::WinLoss, 1))
+                .abs()
                 < TOLERANCE
         );
     }
@@ -1,15 +1,25 @@
 use argmin::{
-    core::{CostFunction, Error, Executor},
+    core::{CostFunction, Executor},
     solver::brent::BrentOpt,
 };
 
-#[derive(Clone)]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    tipping::models::distribution::MarginDistribution,
+};
+
+/// Spread assumed for predictions until there are enough results to estimate it
+const DEFAULT_MARGIN_SD: f64 = 40.0;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MarginModel {
     pub data: MarginData,
     pub k: f64,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MarginData {
     pub probs: Vec<f64>,
     margins: Vec<u32>,
@@ -37,7 +47,7 @@ impl CostFunction for MarginData {
     type Param = f64;
     type Output = f64;
 
-    fn cost(&self, k: &Self::Param) -> Result<Self::Output, Error> {
+    fn cost(&self, k: &Self::Param) -> std::result::Result<Self::Output, argmin::core::Error> {
         Ok(calculate_margin_error(
             *k,
             self.probs.clone(),
@@ -66,15 +76,175 @@ impl MarginModel {
         self.data.correct.push(correct);
     }
 
-    pub fn update(&mut self) {
+    pub fn update(&mut self) -> Result<()> {
         let solver = BrentOpt::new(0.0, 250.0);
 
-        let res = Executor::new(self.clone().data, solver).run().unwrap();
+        let res = Executor::new(self.clone().data, solver).run()?;
 
-        self.k = res.state.param.unwrap()
+        self.k = res.state.param.ok_or(Error::Optimiser(
+            "margin fit returned no parameter".to_string(),
+        ))?;
+        Ok(())
     }
 
     pub fn predict(&self, prob: f64) -> u32 {
         margin_formula(self.k, prob) as u32
     }
+
+    /// Root mean square error of the fitted margins over the results seen so far
+    pub fn residual_sd(&self) -> f64 {
+        let n = self.data.probs.len();
+        if n < 3 {
+            return DEFAULT_MARGIN_SD;
+        }
+        let sse: f64 = self
+            .data
+            .probs
+            .iter()
+            .zip(self.data.margins.iter())
+            .zip(self.data.correct.iter())
+            .map(|((p, m), c)| {
+                let actual = if *c { *m as f64 } else { -(*m as f64) };
+                (margin_formula(self.k, *p) - actual).powi(2)
+            })
+            .sum();
+        (sse / n as f64).sqrt()
+    }
+
+    /// Distribution of the home team's margin given its probability of winning
+    pub fn distribution(&self, home_prob: f64) -> MarginDistribution {
+        let favourite_margin = margin_formula(self.k, home_prob.max(1.0 - home_prob));
+        let mean = if home_prob >= 0.5 {
+            favourite_margin
+        } else {
+            -favourite_margin
+        };
+        MarginDistribution::new(mean, self.residual_sd())
+    }
+}
+
+/// Where `tip_season` takes tip margins from, for models that do not predict scores
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarginMethod {
+    /// `MarginModel`, scaling the tipped team's probability
+    #[default]
+    Probability,
+    /// `RegressionMarginModel`, regressing the signed margin on the rating difference
+    Regression,
+}
+
+/// Regresses the home team's signed margin on the rating difference between the teams.
+/// The intercept captures home ground advantage; sufficient statistics are kept so the
+/// fit updates after every result.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct RegressionMarginModel {
+    n: f64,
+    sum_x: f64,
+    sum_y: f64,
+    sum_xx: f64,
+    sum_xy: f64,
+    sum_yy: f64,
+}
+
+impl RegressionMarginModel {
+    pub fn new() -> RegressionMarginModel {
+        RegressionMarginModel::default()
+    }
+
+    pub fn add_result(&mut self, rating_diff: f64, home_margin: i64) {
+        let y = home_margin as f64;
+        self.n += 1.0;
+        self.sum_x += rating_diff;
+        self.sum_y += y;
+        self.sum_xx += rating_diff.powi(2);
+        self.sum_xy += rating_diff * y;
+        self.sum_yy += y.powi(2);
+    }
+
+    fn s_xx(&self) -> f64 {
+        self.sum_xx - self.sum_x.powi(2) / self.n
+    }
+
+    pub fn slope(&self) -> f64 {
+        if self.n < 2.0 || self.s_xx() <= 0.0 {
+            return 0.0;
+        }
+        (self.sum_xy - self.sum_x * self.sum_y / self.n) / self.s_xx()
+    }
+
+    pub fn intercept(&self) -> f64 {
+        if self.n == 0.0 {
+            return 0.0;
+        }
+        (self.sum_y - self.slope() * self.sum_x) / self.n
+    }
+
+    /// Standard deviation of the residuals about the fitted line
+    pub fn residual_sd(&self) -> f64 {
+        if self.n < 3.0 {
+            return DEFAULT_MARGIN_SD;
+        }
+        let (a, b) = (self.intercept(), self.slope());
+        let sse = self.sum_yy - 2.0 * a * self.sum_y - 2.0 * b * self.sum_xy
+            + self.n * a.powi(2)
+            + 2.0 * a * b * self.sum_x
+            + b.powi(2) * self.sum_xx;
+        (sse.max(0.0) / (self.n - 2.0)).sqrt()
+    }
+
+    pub fn predict(&self, rating_diff: f64) -> MarginDistribution {
+        let margin = self.intercept() + self.slope() * rating_diff;
+        if self.n < 3.0 || self.s_xx() <= 0.0 {
+            return MarginDistribution::new(margin, self.residual_sd());
+        }
+        let mean_x = self.sum_x / self.n;
+        let leverage = 1.0 / self.n + (rating_diff - mean_x).powi(2) / self.s_xx();
+        MarginDistribution::new(margin, self.residual_sd() * (1.0 + leverage).sqrt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 0.001;
+
+    #[test]
+    fn test_regression_margin_model() {
+        let mut model = RegressionMarginModel::new();
+        assert_eq!(model.predict(50.0).sd, DEFAULT_MARGIN_SD);
+
+        for (diff, margin) in [
+            (-100.0, -40),
+            (-50.0, -14),
+            (0.0, 6),
+            (50.0, 26),
+            (100.0, 52),
+        ] {
+            model.add_result(diff, margin);
+        }
+
+        assert!((model.slope() - 0.448).abs() < TOLERANCE);
+        assert!((model.intercept() - 6.0).abs() < TOLERANCE);
+        let p = model.predict(-20.0);
+        assert!((p.mean + 2.96).abs() < TOLERANCE);
+        assert!(p.sd > model.residual_sd());
+        assert!(model.predict(200.0).sd > p.sd);
+    }
+
+    #[test]
+    fn test_margin_distribution() {
+        let mut model = MarginModel::new(Some(100.0));
+        assert_eq!(model.distribution(0.6).sd, DEFAULT_MARGIN_SD);
+        model.add_result(0.6, 10, true);
+        model.add_result(0.7, 30, false);
+        model.add_result(0.8, 30, true);
+
+        let home_favourite = model.distribution(0.7);
+        let away_favourite = model.distribution(0.3);
+        assert!((home_favourite.mean - 20.0).abs() < TOLERANCE);
+        assert!((away_favourite.mean + 20.0).abs() < TOLERANCE);
+        // Errors of 0, 50 and 0 points
+        assert!((home_favourite.sd - (2500.0f64 / 3.0).sqrt()).abs() < TOLERANCE);
+    }
 }
@@ -0,0 +1,291 @@
+use crate::{
+    error::{Error, Result},
+    tipping::{
+        models::{
+            distribution::{normal_cdf, MarginDistribution},
+            snapshot, RatingModel, SeasonRegression, TeamRating,
+        },
+        teams::TeamId,
+        Match, MatchPrediction, MatchResult,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A team's scoring shot strengths, on a log scale relative to the league average
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringShotTeamStats {
+    /// Log multiple of average shots generated
+    pub attack: f64,
+    /// Log multiple of average shots prevented
+    pub defence: f64,
+    /// Fraction of the team's scoring shots that are goals
+    pub conversion: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringShotModel {
+    pub model_stats: HashMap<String, ScoringShotTeamStats>,
+    pub model_params: ScoringShotModelParams,
+}
+
+#[derive(Debug, Default)]
+pub struct ScoringShotModelInitParams {
+    pub teams: HashSet<String>,
+    /// League average scoring shots per team per match
+    pub mean_shots: Option<f64>,
+    /// League average conversion rate
+    pub mean_conversion: Option<f64>,
+    /// Home advantage as a log multiple of shots generated
+    pub home_advantage: Option<f64>,
+    /// Step size for attack and defence updates
+    pub learning_rate: Option<f64>,
+    /// Step size for conversion rate updates
+    pub conversion_learning_rate: Option<f64>,
+    /// Inflation of the score spread over a compound Poisson model
+    pub dispersion: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringShotModelParams {
+    pub teams: HashSet<String>,
+    pub mean_shots: f64,
+    pub mean_conversion: f64,
+    pub home_advantage: f64,
+    pub learning_rate: f64,
+    pub conversion_learning_rate: f64,
+    pub dispersion: f64,
+}
+
+/// Expected scoring shots and scores for a match
+#[derive(Debug, Clone, Copy)]
+pub struct ScoringShotPrediction {
+    pub home_shots: f64,
+    pub away_shots: f64,
+    pub home_score: f64,
+    pub away_score: f64,
+    /// Standard deviation of the home team's margin
+    pub margin_sd: f64,
+}
+
+impl ScoringShotPrediction {
+    pub fn margin(&self) -> f64 {
+        self.home_score - self.away_score
+    }
+}
+
+impl ScoringShotModel {
+    pub fn new(params: ScoringShotModelInitParams) -> ScoringShotModel {
+        const DEFAULT_MEAN_SHOTS: f64 = 25.0;
+        const DEFAULT_MEAN_CONVERSION: f64 = 0.53;
+        const DEFAULT_HOME_ADVANTAGE: f64 = 0.04;
+        const DEFAULT_LEARNING_RATE: f64 = 0.1;
+        const DEFAULT_CONVERSION_LEARNING_RATE: f64 = 0.02;
+        const DEFAULT_DISPERSION: f64 = 1.2;
+
+        let mut model = ScoringShotModel {
+            model_stats: HashMap::new(),
+            model_params: ScoringShotModelParams {
+                teams: HashSet::new(),
+                mean_shots: params.mean_shots.unwrap_or(DEFAULT_MEAN_SHOTS),
+                mean_conversion: params.mean_conversion.unwrap_or(DEFAULT_MEAN_CONVERSION),
+                home_advantage: params.home_advantage.unwrap_or(DEFAULT_HOME_ADVANTAGE),
+                learning_rate: params.learning_rate.unwrap_or(DEFAULT_LEARNING_RATE),
+                conversion_learning_rate: params
+                    .conversion_learning_rate
+                    .unwrap_or(DEFAULT_CONVERSION_LEARNING_RATE),
+                dispersion: params.dispersion.unwrap_or(DEFAULT_DISPERSION),
+            },
+        };
+        for team in params.teams {
+            model.add_team(None, &team);
+        }
+        model
+    }
+
+    fn team_stats(&self, team: &str) -> Result<&ScoringShotTeamStats> {
+        self.model_stats
+            .get(team)
+            .ok_or(Error::UnknownTeam(team.to_string()))
+    }
+
+    /// Expected shots for (home, away)
+    fn expected_shots(&self, match_: &Match) -> Result<(f64, f64)> {
+        let home = self.team_stats(&match_.home_team)?;
+        let away = self.team_stats(&match_.away_team)?;
+        let params = &self.model_params;
+        Ok((
+            params.mean_shots * (home.attack - away.defence + params.home_advantage).exp(),
+            params.mean_shots * (away.attack - home.defence).exp(),
+        ))
+    }
+
+    pub fn predict_shots(&self, match_: &Match) -> Result<ScoringShotPrediction> {
+        let (home_shots, away_shots) = self.expected_shots(match_)?;
+        let home_conversion = self.team_stats(&match_.home_team)?.conversion;
+        let away_conversion = self.team_stats(&match_.away_team)?.conversion;
+
+        // Each shot is worth 6 with probability c and 1 otherwise; shot counts are
+        // treated as Poisson, so each score is compound Poisson
+        let mean_points = |c: f64| 1.0 + 5.0 * c;
+        let mean_square_points = |c: f64| 1.0 + 35.0 * c;
+        let variance = home_shots * mean_square_points(home_conversion)
+            + away_shots * mean_square_points(away_conversion);
+
+        Ok(ScoringShotPrediction {
+            home_shots,
+            away_shots,
+            home_score: home_shots * mean_points(home_conversion),
+            away_score: away_shots * mean_points(away_conversion),
+            margin_sd: variance.sqrt() * self.model_params.dispersion,
+        })
+    }
+}
+
+impl RatingModel for ScoringShotModel {
+    fn predict(&self, match_: &Match) -> Result<MatchPrediction> {
+        let p = self.predict_shots(match_)?;
+        let home_team_win_prob = normal_cdf(p.margin() / p.margin_sd);
+        Ok(MatchPrediction {
+            prediction: home_team_win_prob,
+            pred_margin: 0,
+            home_team_win: home_team_win_prob >= 0.5,
+            home_score: Some(p.home_score),
+            away_score: Some(p.away_score),
+            margin_distribution: Some(MarginDistribution::new(p.margin(), p.margin_sd)),
+        })
+    }
+
+    /// Results without a goals and behinds breakdown leave the ratings unchanged
+    fn update(&mut self, match_: &Match, match_result: &MatchResult) -> Result<()> {
+        let (Some(home_score), Some(away_score)) =
+            (match_result.home_score, match_result.away_score)
+        else {
+            return Ok(());
+        };
+        let (home_shots, away_shots) = self.expected_shots(match_)?;
+        let lr = self.model_params.learning_rate;
+        let conversion_lr = self.model_params.conversion_learning_rate;
+        // Half a shot of smoothing keeps a scoreless side finite on the log scale
+        let home_error = ((home_score.shots() as f64 + 0.5) / (home_shots + 0.5)).ln();
+        let away_error = ((away_score.shots() as f64 + 0.5) / (away_shots + 0.5)).ln();
+
+        let home = self
+            .model_stats
+            .get_mut(&match_.home_team)
+            .ok_or_else(|| Error::UnknownTeam(match_.home_team.clone()))?;
+        home.attack += lr * home_error;
+        home.defence -= lr * away_error;
+        if home_score.shots() > 0 {
+            let rate = home_score.goals as f64 / home_score.shots() as f64;
+            home.conversion += conversion_lr * (rate - home.conversion);
+        }
+
+        let away = self
+            .model_stats
+            .get_mut(&match_.away_team)
+            .ok_or_else(|| Error::UnknownTeam(match_.away_team.clone()))?;
+        away.attack += lr * away_error;
+        away.defence -= lr * home_error;
+        if away_score.shots() > 0 {
+            let rate = away_score.goals as f64 / away_score.shots() as f64;
+            away.conversion += conversion_lr * (rate - away.conversion);
+        }
+        Ok(())
+    }
+
+    /// Expected points margin against an average team at a neutral venue
+    fn team_ratings(&self) -> Vec<TeamRating> {
+        let params = &self.model_params;
+        let average_points = 1.0 + 5.0 * params.mean_conversion;
+        self.model_stats
+            .iter()
+            .map(|(team, stats)| TeamRating {
+                team: team.clone(),
+                rating: params.mean_shots * stats.attack.exp() * (1.0 + 5.0 * stats.conversion)
+                    - params.mean_shots * (-stats.defence).exp() * average_points,
+                rd: None,
+                volatility: None,
+            })
+            .collect()
+    }
+
+    fn add_team(&mut self, _id: Option<TeamId>, team: &str) {
+        if self.model_stats.contains_key(team) {
+            return;
+        }
+        self.model_params.teams.insert(team.to_string());
+        self.model_stats.insert(
+            team.to_string(),
+            ScoringShotTeamStats {
+                attack: 0.0,
+                defence: 0.0,
+                conversion: self.model_params.mean_conversion,
+            },
+        );
+    }
+
+    fn new_season(&mut self, regression: &SeasonRegression) {
+        let mean_conversion = self.model_params.mean_conversion;
+        for stats in self.model_stats.values_mut() {
+            stats.attack -= stats.attack * regression.rating_shrink;
+            stats.defence -= stats.defence * regression.rating_shrink;
+            stats.conversion -= (stats.conversion - mean_conversion) * regression.rating_shrink;
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        snapshot::to_json(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::{fixture, Score, Team};
+
+    const TOLERANCE: f64 = 0.001;
+
+    #[test]
+    fn test_scoring_shot_model() {
+        let mut model = ScoringShotModel::new(ScoringShotModelInitParams {
+            teams: HashSet::from(["A".to_string(), "B".to_string()]),
+            home_advantage: Some(0.0),
+            ..Default::default()
+        });
+        let match_ = fixture("A", "B");
+        assert!((model.predict(&match_).unwrap().prediction - 0.5).abs() < TOLERANCE);
+
+        // A kicks straight from few shots, B sprays plenty: A wins 14.6 (90) to 10.20 (80)
+        // but B's shots count
+        let result = MatchResult {
+            winning_team: Some(Team {
+                name: "A".to_string(),
+            }),
+            winning_margin: Some(10),
+            draw: false,
+            home_team_won: true,
+            away_team_won: false,
+            home_points: 90,
+            away_points: 80,
+            home_score: Some(Score {
+                goals: 14,
+                behinds: 6,
+            }),
+            away_score: Some(Score {
+                goals: 10,
+                behinds: 20,
+            }),
+        };
+        for _ in 0..5 {
+            model.update(&match_, &result).unwrap();
+        }
+
+        let stats = &model.model_stats;
+        assert!(stats["B"].attack > stats["A"].attack);
+        assert!(stats["A"].conversion > stats["B"].conversion);
+        let p = model.predict_shots(&match_).unwrap();
+        assert!(p.away_shots > p.home_shots);
+        assert!(p.margin_sd > 0.0);
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Version written to new snapshots; older versions are rejected on load
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// Versioned envelope around a saved model
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot<T> {
+    pub version: u32,
+    /// Unix timestamp the snapshot was written at
+    pub saved_at: i64,
+    pub model: T,
+}
+
+impl<T> Snapshot<T> {
+    pub fn new(model: T) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            saved_at: chrono::Utc::now().timestamp(),
+            model,
+        }
+    }
+}
+
+/// Serialise a model (or a tuple of models) to plain JSON
+pub fn to_json<T: Serialize>(model: &T) -> Result<String> {
+    Ok(serde_json::to_string_pretty(model)?)
+}
+
+/// Deserialise a model from plain JSON
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Write a model to `path` inside a versioned snapshot envelope
+pub fn save<T: Serialize>(model: &T, path: impl AsRef<Path>) -> Result<()> {
+    let json = to_json(&Snapshot::new(model))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+/// Read a model previously written by [`save`]
+pub fn load<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T> {
+    let json = std::fs::read_to_string(path)?;
+    let snapshot: Snapshot<T> = from_json(&json)?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        return Err(Error::Parse(format!(
+            "unsupported snapshot version {} (expected {})",
+            snapshot.version, SNAPSHOT_VERSION
+        )));
+    }
+    Ok(snapshot.model)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::tipping::models::{
+        glicko::{GlickoModel, GlickoModelInitParams},
+        margin::MarginModel,
+    };
+
+    #[test]
+    fn test_save_and_load() {
+        let model = GlickoModel::new(GlickoModelInitParams {
+            teams: HashSet::from(["A".to_string(), "B".to_string()]),
+            starting_rd: None,
+            starting_volatility: None,
+            starting_elo: None,
+            offsets: None,
+            scale_factor: None,
+            volatility_constraint: None,
+            rating_period_days: None,
+            home_advantage: None,
+            venue_offsets: None,
+            outcome: None,
+        })
+        .unwrap();
+        let path = std::env::temp_dir().join("afl_snapshot_test.json");
+
+        save(&(model.clone(), MarginModel::new(Some(100.0))), &path).unwrap();
+        let (loaded, margin_model): (GlickoModel, MarginModel) = load(&path).unwrap();
+
+        assert_eq!(loaded.model_stats.len(), 2);
+        assert_eq!(
+            loaded.stats("A").unwrap().elo,
+            model.stats("A").unwrap().elo
+        );
+        assert_eq!(margin_model.k, 100.0);
+
+        std::fs::write(&path, r#"{"version": 0, "saved_at": 0, "model": null}"#).unwrap();
+        assert!(matches!(
+            load::<Option<GlickoModel>>(&path),
+            Err(Error::Parse(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
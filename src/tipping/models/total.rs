@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Tracks the league's typical combined score, to split a predicted margin into
+/// a score for each team when the rating model does not predict scores itself
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TotalModel {
+    pub mean_total: f64,
+    pub learning_rate: f64,
+}
+
+impl TotalModel {
+    pub fn new(mean_total: Option<f64>) -> TotalModel {
+        const DEFAULT_MEAN_TOTAL: f64 = 165.0;
+        const LEARNING_RATE: f64 = 0.05;
+
+        TotalModel {
+            mean_total: mean_total.unwrap_or(DEFAULT_MEAN_TOTAL),
+            learning_rate: LEARNING_RATE,
+        }
+    }
+
+    pub fn add_result(&mut self, total: u32) {
+        self.mean_total += self.learning_rate * (total as f64 - self.mean_total);
+    }
+
+    /// Split the expected total around the home team's predicted margin
+    pub fn predict_scores(&self, home_margin: f64) -> (f64, f64) {
+        (
+            (self.mean_total + home_margin) / 2.0,
+            (self.mean_total - home_margin) / 2.0,
+        )
+    }
+}
@@ -0,0 +1,138 @@
+use std::{future::Future, path::PathBuf};
+
+use crate::{
+    error::{Error, Result},
+    tipping::{
+        squiggle::{
+            get_squiggle_projected_ladder, get_squiggle_season, get_squiggle_sources,
+            get_squiggle_standings, get_squiggle_team_list, get_squiggle_teams,
+            get_squiggle_tips, parse_squiggle_games, SquiggleLadderEntry, SquiggleSource,
+            SquiggleStanding, SquiggleTeam, SquiggleTip,
+        },
+        SquiggleMatch,
+    },
+};
+
+/// A source of season fixtures and results in the Squiggle `games` format
+pub trait MatchSource {
+    fn get_season(&self, year: i32) -> impl Future<Output = Result<Vec<SquiggleMatch>>>;
+
+    /// Clubs that played in `year`. Defaults to the teams named in that season's games.
+    fn get_teams(&self, year: i32) -> impl Future<Output = Result<Vec<SquiggleTeam>>> {
+        async move { Ok(get_squiggle_teams(&self.get_season(year).await?)) }
+    }
+}
+
+/// Live Squiggle API, with responses cached through `request_cache`
+#[derive(Debug, Clone)]
+pub struct SquiggleApi {
+    pub user_agent: String,
+    pub cache_session: String,
+}
+
+impl SquiggleApi {
+    pub fn new(user_agent: String, cache_session: Option<String>) -> Self {
+        Self {
+            user_agent,
+            cache_session: cache_session.unwrap_or("squiggle_cache".to_string()),
+        }
+    }
+
+    /// Every public model's tips for `year`
+    pub async fn get_tips(&self, year: i32) -> Result<Vec<SquiggleTip>> {
+        get_squiggle_tips(year, self.user_agent.clone(), self.cache_session.clone()).await
+    }
+
+    /// The ladder for `year`, after `round` if given or as it stands otherwise
+    pub async fn get_standings(&self, year: i32, round: Option<i32>) -> Result<Vec<SquiggleStanding>> {
+        get_squiggle_standings(year, round, self.user_agent.clone(), self.cache_session.clone()).await
+    }
+
+    /// Every source's projected final ladder for `year`
+    pub async fn get_projected_ladder(&self, year: i32, round: Option<i32>) -> Result<Vec<SquiggleLadderEntry>> {
+        get_squiggle_projected_ladder(year, round, self.user_agent.clone(), self.cache_session.clone()).await
+    }
+
+    /// Models and tipsters that publish to Squiggle
+    pub async fn get_sources(&self) -> Result<Vec<SquiggleSource>> {
+        get_squiggle_sources(self.user_agent.clone(), self.cache_session.clone()).await
+    }
+}
+
+impl MatchSource for SquiggleApi {
+    async fn get_season(&self, year: i32) -> Result<Vec<SquiggleMatch>> {
+        get_squiggle_season(year, self.user_agent.clone(), self.cache_session.clone()).await
+    }
+
+    /// Clubs that played in `year`, with their Squiggle team IDs
+    async fn get_teams(&self, year: i32) -> Result<Vec<SquiggleTeam>> {
+        get_squiggle_team_list(year, self.user_agent.clone(), self.cache_session.clone()).await
+    }
+}
+
+/// Directory of saved Squiggle responses, one `<year>.json` file per season
+#[derive(Debug, Clone)]
+pub struct JsonDirSource {
+    pub dir: PathBuf,
+}
+
+impl JsonDirSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl MatchSource for JsonDirSource {
+    async fn get_season(&self, year: i32) -> Result<Vec<SquiggleMatch>> {
+        let path = self.dir.join(format!("{}.json", year));
+        let body = std::fs::read_to_string(&path)
+            .map_err(|e| Error::Fetch(format!("{}: {}", path.display(), e)))?;
+        parse_squiggle_games(&body)
+    }
+}
+
+/// In-memory matches, selected by their `year` field
+#[derive(Debug, Clone, Default)]
+pub struct VecSource {
+    pub matches: Vec<SquiggleMatch>,
+}
+
+impl VecSource {
+    pub fn new(matches: Vec<SquiggleMatch>) -> Self {
+        Self { matches }
+    }
+}
+
+impl MatchSource for VecSource {
+    async fn get_season(&self, year: i32) -> Result<Vec<SquiggleMatch>> {
+        Ok(self
+            .matches
+            .iter()
+            .filter(|x| x.year == Some(year))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tipping::game;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_teams_from_games() {
+        let mut with_ids = game(1, 2024, 0, ("Sydney", "Adelaide"), Some((90, 80)));
+        with_ids.hteamid = Some(16);
+        let source = VecSource::new(vec![
+            with_ids,
+            game(2, 2024, 1, ("Adelaide", "Carlton"), None),
+            game(3, 2023, 0, ("Geelong", "Sydney"), None),
+        ]);
+
+        let teams = block_on(source.get_teams(2024)).unwrap();
+
+        let teams: Vec<(i32, &str)> = teams.iter().map(|x| (x.id, x.name.as_str())).collect();
+        assert_eq!(teams, vec![(0, "Adelaide"), (0, "Carlton"), (16, "Sydney")]);
+    }
+}
@@ -1,12 +1,115 @@
-use std::collections::HashSet;
+use std::collections::BTreeMap;
 
 use request_cache::cached_request;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
-use crate::tipping::SquiggleMatch;
+use crate::{
+    error::{Error, Result},
+    tipping::SquiggleMatch,
+};
 
-async fn call_squiggle_season(year: i32, user_agent: String, cache_session: String) -> String {
-    let url = format!("https://api.squiggle.com.au/?q=games;year={}", year);
+/// One model's tip for one game, from the Squiggle `tips` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SquiggleTip {
+    pub gameid: i32,
+    pub sourceid: i32,
+    pub source: String,
+    pub year: Option<i32>,
+    pub round: Option<i32>,
+    pub hteam: Option<String>,
+    pub ateam: Option<String>,
+    /// Name of the tipped team
+    pub tip: Option<String>,
+    /// Probability of a home win, as a percentage
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub hconfidence: Option<f64>,
+    /// Predicted margin in favour of the tipped team
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub margin: Option<f64>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub bits: Option<f64>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub err: Option<f64>,
+    pub correct: Option<i32>,
+}
+
+/// A club from the Squiggle `teams` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SquiggleTeam {
+    pub id: i32,
+    pub name: String,
+    pub abbrev: Option<String>,
+    pub logo: Option<String>,
+    pub debut: Option<i32>,
+    pub retirement: Option<i32>,
+}
+
+/// A team's line on the actual ladder, from the Squiggle `standings` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SquiggleStanding {
+    pub id: i32,
+    pub name: String,
+    pub rank: i32,
+    #[serde(default)]
+    pub played: i32,
+    #[serde(default)]
+    pub wins: i32,
+    #[serde(default)]
+    pub losses: i32,
+    #[serde(default)]
+    pub draws: i32,
+    /// Premiership points
+    #[serde(default)]
+    pub pts: i32,
+    #[serde(rename = "for", default)]
+    pub points_for: i32,
+    #[serde(rename = "against", default)]
+    pub points_against: i32,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub percentage: Option<f64>,
+}
+
+/// One source's projected finishing position for a team, from the Squiggle `ladder` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SquiggleLadderEntry {
+    pub source: String,
+    pub sourceid: i32,
+    pub team: String,
+    pub teamid: i32,
+    pub year: Option<i32>,
+    pub round: Option<i32>,
+    pub rank: Option<i32>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub mean_rank: Option<f64>,
+    /// Projected wins at the end of the home-and-away season
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub wins: Option<f64>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub percentage: Option<f64>,
+    pub updated: Option<String>,
+}
+
+/// A model or tipster that publishes to Squiggle, from the `sources` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SquiggleSource {
+    pub id: i32,
+    pub name: String,
+    pub url: Option<String>,
+    pub icon: Option<String>,
+}
+
+/// Squiggle sends some numbers as JSON strings, and some as null
+fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<f64>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    })
+}
+
+async fn call_squiggle(query: String, user_agent: String, cache_session: String) -> String {
+    let url = format!("https://api.squiggle.com.au/?q={}", query);
     let resp = cached_request(
         url,
         "GET".to_string(),
@@ -18,21 +121,155 @@ async fn call_squiggle_season(year: i32, user_agent: String, cache_session: Stri
     resp.response
 }
 
-pub async fn get_squiggle_season(year: i32, user_agent: String, cache_session: String) -> Vec<SquiggleMatch> {
-    let body = call_squiggle_season(year, user_agent, cache_session).await;
-    let v: Value = serde_json::from_str(&body).unwrap();
-    serde_json::from_value(v.get("games").unwrap().clone()).unwrap()
+pub async fn get_squiggle_season(year: i32, user_agent: String, cache_session: String) -> Result<Vec<SquiggleMatch>> {
+    let body = call_squiggle(format!("games;year={}", year), user_agent, cache_session).await;
+    parse_squiggle_games(&body)
+}
+
+pub async fn get_squiggle_tips(year: i32, user_agent: String, cache_session: String) -> Result<Vec<SquiggleTip>> {
+    let body = call_squiggle(format!("tips;year={}", year), user_agent, cache_session).await;
+    parse_squiggle_tips(&body)
+}
+
+/// Clubs that played in `year`, with their Squiggle team IDs
+pub async fn get_squiggle_team_list(year: i32, user_agent: String, cache_session: String) -> Result<Vec<SquiggleTeam>> {
+    let body = call_squiggle(format!("teams;year={}", year), user_agent, cache_session).await;
+    parse_squiggle_response(&body, "teams")
+}
+
+/// The ladder for `year`, after `round` if given or as it stands otherwise
+pub async fn get_squiggle_standings(
+    year: i32,
+    round: Option<i32>,
+    user_agent: String,
+    cache_session: String,
+) -> Result<Vec<SquiggleStanding>> {
+    let body = call_squiggle(with_round(format!("standings;year={}", year), round), user_agent, cache_session).await;
+    parse_squiggle_response(&body, "standings")
+}
+
+/// Every source's projected final ladder for `year`, as made after `round` if given
+pub async fn get_squiggle_projected_ladder(
+    year: i32,
+    round: Option<i32>,
+    user_agent: String,
+    cache_session: String,
+) -> Result<Vec<SquiggleLadderEntry>> {
+    let body = call_squiggle(with_round(format!("ladder;year={}", year), round), user_agent, cache_session).await;
+    parse_squiggle_response(&body, "ladder")
+}
+
+pub async fn get_squiggle_sources(user_agent: String, cache_session: String) -> Result<Vec<SquiggleSource>> {
+    let body = call_squiggle("sources".to_string(), user_agent, cache_session).await;
+    parse_squiggle_response(&body, "sources")
 }
 
-pub fn get_squiggle_teams(squiggle_games: &Vec<SquiggleMatch>) -> HashSet<String> {
-    let mut names = HashSet::new();
+fn with_round(query: String, round: Option<i32>) -> String {
+    match round {
+        Some(round) => format!("{};round={}", query, round),
+        None => query,
+    }
+}
+
+/// Deserialise the list under `key` in a Squiggle response
+pub fn parse_squiggle_response<T: DeserializeOwned>(body: &str, key: &str) -> Result<Vec<T>> {
+    let v: Value = serde_json::from_str(body)?;
+    let items = v
+        .get(key)
+        .ok_or(Error::Parse(format!("response has no {}", key)))?;
+    Ok(serde_json::from_value(items.clone())?)
+}
+
+pub fn parse_squiggle_games(body: &str) -> Result<Vec<SquiggleMatch>> {
+    parse_squiggle_response(body, "games")
+}
+
+pub fn parse_squiggle_tips(body: &str) -> Result<Vec<SquiggleTip>> {
+    parse_squiggle_response(body, "tips")
+}
+
+/// The teams named in a set of games, in name order, with the Squiggle IDs the games give
+/// them (0 if none). This is the fallback for sources that cannot list teams themselves.
+pub fn get_squiggle_teams(squiggle_games: &[SquiggleMatch]) -> Vec<SquiggleTeam> {
+    let mut ids: BTreeMap<String, i32> = BTreeMap::new();
     for game in squiggle_games {
-        if let Some(name) = &game.ateam {
-            names.insert(name.clone());
-        }
-        if let Some(name) = &game.hteam {
-            names.insert(name.clone());
+        for (name, id) in [(&game.hteam, game.hteamid), (&game.ateam, game.ateamid)] {
+            if let Some(name) = name {
+                let known = ids.entry(name.clone()).or_insert(0);
+                *known = (*known).max(id.unwrap_or(0));
+            }
         }
     }
-    names
+    ids.into_iter()
+        .map(|(name, id)| SquiggleTeam {
+            id,
+            name,
+            abbrev: None,
+            logo: None,
+            debut: None,
+            retirement: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_squiggle_tips() {
+        let body = r#"{"tips": [
+            {"gameid": 1, "sourceid": 8, "source": "Aggregate", "year": 2024, "round": 1,
+             "hteam": "Sydney", "ateam": "Melbourne", "tip": "Sydney",
+             "hconfidence": "62.50", "margin": "11.2", "bits": "0.3219", "err": "8.8", "correct": 1},
+            {"gameid": 2, "sourceid": 9, "source": "Punter", "tip": "Carlton",
+             "hconfidence": 40, "margin": null, "bits": null, "correct": null}
+        ]}"#;
+
+        let tips = parse_squiggle_tips(body).unwrap();
+
+        assert_eq!(tips.len(), 2);
+        assert_eq!(tips[0].hconfidence, Some(62.5));
+        assert_eq!(tips[0].margin, Some(11.2));
+        assert_eq!(tips[1].hconfidence, Some(40.0));
+        assert_eq!(tips[1].margin, None);
+        assert_eq!(tips[1].err, None);
+        assert!(parse_squiggle_tips(r#"{"games": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_squiggle_endpoints() {
+        let teams: Vec<SquiggleTeam> = parse_squiggle_response(
+            r#"{"teams": [{"id": 1, "name": "Adelaide", "abbrev": "ADE", "logo": "/adelaide.png", "debut": 1991, "retirement": 9999}]}"#,
+            "teams",
+        )
+        .unwrap();
+        assert_eq!((teams[0].id, teams[0].abbrev.as_deref()), (1, Some("ADE")));
+
+        let standings: Vec<SquiggleStanding> = parse_squiggle_response(
+            r#"{"standings": [{"id": 16, "name": "Sydney", "rank": 1, "played": 5, "wins": 4, "losses": 1, "draws": 0,
+                "pts": 16, "for": 500, "against": 400, "percentage": 125}]}"#,
+            "standings",
+        )
+        .unwrap();
+        assert_eq!(standings[0].points_for, 500);
+        assert_eq!(standings[0].percentage, Some(125.0));
+
+        let ladder: Vec<SquiggleLadderEntry> = parse_squiggle_response(
+            r#"{"ladder": [{"source": "Aggregate", "sourceid": 8, "team": "Sydney", "teamid": 16, "year": 2024,
+                "round": 5, "rank": 1, "mean_rank": "1.667", "wins": "17.2", "percentage": "125.3"}]}"#,
+            "ladder",
+        )
+        .unwrap();
+        assert_eq!(ladder[0].wins, Some(17.2));
+        assert_eq!(ladder[0].updated, None);
+
+        let sources: Vec<SquiggleSource> = parse_squiggle_response(
+            r#"{"sources": [{"id": 1, "name": "Squiggle", "url": "https://squiggle.com.au", "icon": null}]}"#,
+            "sources",
+        )
+        .unwrap();
+        assert_eq!(sources[0].name, "Squiggle");
+        assert!(parse_squiggle_response::<SquiggleSource>(r#"{"teams": []}"#, "sources").is_err());
+    }
 }
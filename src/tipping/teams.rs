@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+/// IDs given to teams the registry learns by name alone, clear of Squiggle's own IDs
+const FIRST_LOCAL_ID: u32 = 1000;
+
+/// Stable identity of a club, using Squiggle's team ID where there is one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TeamId(pub u32);
+
+impl TeamId {
+    /// Squiggle's `hteamid`/`ateamid`, which are missing or zero for unknown teams
+    pub fn from_squiggle(id: Option<i32>) -> Option<TeamId> {
+        id.filter(|x| *x > 0).map(|x| TeamId(x as u32))
+    }
+}
+
+/// A club's canonical name, abbreviation, and any other names it is known by
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamInfo {
+    pub id: TeamId,
+    pub name: String,
+    pub abbrev: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+impl TeamInfo {
+    fn known_as(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+            || self
+                .abbrev
+                .as_ref()
+                .is_some_and(|x| x.eq_ignore_ascii_case(name))
+            || self.aliases.iter().any(|x| x.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Maps the names, abbreviations and IDs that sources use for a club onto one `TeamId`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamRegistry {
+    teams: Vec<TeamInfo>,
+}
+
+impl Default for TeamRegistry {
+    fn default() -> Self {
+        TeamRegistry::afl()
+    }
+}
+
+impl TeamRegistry {
+    pub fn empty() -> TeamRegistry {
+        TeamRegistry { teams: Vec::new() }
+    }
+
+    /// The current AFL clubs under their Squiggle IDs and names, with former names as aliases
+    pub fn afl() -> TeamRegistry {
+        let clubs: [(u32, &str, &str, &[&str]); 18] = [
+            (1, "Adelaide", "ADE", &["Adelaide Crows"]),
+            (2, "Brisbane Lions", "BRI", &["Brisbane", "Brisbane Bears"]),
+            (3, "Carlton", "CAR", &[]),
+            (4, "Collingwood", "COL", &[]),
+            (5, "Essendon", "ESS", &[]),
+            (6, "Fremantle", "FRE", &[]),
+            (7, "Geelong", "GEE", &["Geelong Cats"]),
+            (8, "Gold Coast", "GCS", &["Gold Coast Suns"]),
+            (
+                9,
+                "Greater Western Sydney",
+                "GWS",
+                &["GWS Giants", "Greater Western Sydney Giants"],
+            ),
+            (10, "Hawthorn", "HAW", &[]),
+            (11, "Melbourne", "MEL", &[]),
+            (12, "North Melbourne", "NTH", &["Kangaroos"]),
+            (13, "Port Adelaide", "POR", &[]),
+            (14, "Richmond", "RIC", &[]),
+            (15, "St Kilda", "STK", &[]),
+            (16, "Sydney", "SYD", &["Sydney Swans", "South Melbourne"]),
+            (17, "West Coast", "WCE", &["West Coast Eagles"]),
+            (18, "Western Bulldogs", "WBD", &["Footscray"]),
+        ];
+        TeamRegistry {
+            teams: clubs
+                .iter()
+                .map(|(id, name, abbrev, aliases)| TeamInfo {
+                    id: TeamId(*id),
+                    name: name.to_string(),
+                    abbrev: Some(abbrev.to_string()),
+                    aliases: aliases.iter().map(|x| x.to_string()).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, id: TeamId) -> Option<&TeamInfo> {
+        self.teams.iter().find(|x| x.id == id)
+    }
+
+    /// Canonical name of `id`
+    pub fn name(&self, id: TeamId) -> Option<&str> {
+        self.get(id).map(|x| x.name.as_str())
+    }
+
+    /// Look a team up by its name, abbreviation or an alias, ignoring case
+    pub fn id(&self, name: &str) -> Option<TeamId> {
+        self.teams.iter().find(|x| x.known_as(name)).map(|x| x.id)
+    }
+
+    /// The team a source refers to, by its ID when the registry knows it and its name otherwise
+    pub fn find(&self, id: Option<TeamId>, name: &str) -> Option<TeamId> {
+        id.filter(|x| self.get(*x).is_some())
+            .or_else(|| self.id(name))
+    }
+
+    /// As `find`, registering the team if it is new. A new name for a known ID is kept as
+    /// an alias, and a team with neither gets an ID of its own.
+    pub fn resolve(&mut self, id: Option<TeamId>, name: &str) -> TeamId {
+        if let Some(found) = self.find(id, name) {
+            if self.id(name).is_none() {
+                self.add_alias(found, name);
+            }
+            return found;
+        }
+        let id = id.unwrap_or_else(|| self.next_local_id());
+        self.teams.push(TeamInfo {
+            id,
+            name: name.to_string(),
+            abbrev: None,
+            aliases: Vec::new(),
+        });
+        id
+    }
+
+    pub fn add_alias(&mut self, id: TeamId, alias: &str) {
+        if let Some(team) = self.teams.iter_mut().find(|x| x.id == id) {
+            if !team.known_as(alias) {
+                team.aliases.push(alias.to_string());
+            }
+        }
+    }
+
+    fn next_local_id(&self) -> TeamId {
+        let next = self.teams.iter().map(|x| x.id.0 + 1).max().unwrap_or(0);
+        TeamId(next.max(FIRST_LOCAL_ID))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_team_registry() {
+        let mut registry = TeamRegistry::afl();
+        let gws = TeamId(9);
+        assert_eq!(registry.id("Greater Western Sydney"), Some(gws));
+        assert_eq!(registry.id("gws"), Some(gws));
+        assert_eq!(registry.id("GWS Giants"), Some(gws));
+        assert_eq!(registry.id("Brisbane Bears"), Some(TeamId(2)));
+        assert_eq!(registry.name(TeamId(18)), Some("Western Bulldogs"));
+
+        // A renamed club keeps its ID and learns the new name
+        assert_eq!(registry.resolve(Some(gws), "Giants"), gws);
+        assert_eq!(registry.id("Giants"), Some(gws));
+        assert_eq!(registry.name(gws), Some("Greater Western Sydney"));
+
+        // An unknown ID falls back to the name
+        assert_eq!(registry.find(Some(TeamId(99)), "Sydney"), Some(TeamId(16)));
+
+        let a = registry.resolve(None, "A");
+        let b = registry.resolve(None, "B");
+        assert_eq!(a, TeamId(1000));
+        assert_eq!(b, TeamId(1001));
+        assert_eq!(registry.resolve(None, "A"), a);
+        assert_eq!(registry.find(None, "C"), None);
+
+        // A new club is registered under its Squiggle ID
+        assert_eq!(registry.resolve(Some(TeamId(19)), "Tasmania"), TeamId(19));
+        assert_eq!(registry.id("tasmania"), Some(TeamId(19)));
+    }
+}
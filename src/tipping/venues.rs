@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// Australian state or territory a venue or club is based in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum State {
+    Vic,
+    Nsw,
+    Qld,
+    Sa,
+    Wa,
+    Tas,
+    Act,
+    Nt,
+}
+
+/// State of a venue, keyed on the names Squiggle uses
+pub fn venue_state(venue: &str) -> Option<State> {
+    match venue {
+        "M.C.G." | "Docklands" | "Marvel Stadium" | "Kardinia Park" | "Eureka Stadium"
+        | "Mars Stadium" | "Princes Park" | "Waverley Park" => Some(State::Vic),
+        "S.C.G." | "Sydney Showground" | "Stadium Australia" | "Blacktown" => Some(State::Nsw),
+        "Gabba"
+        | "Carrara"
+        | "Cazaly's Stadium"
+        | "Riverway Stadium"
+        | "Heritage Bank Stadium"
+        | "People First Stadium" => Some(State::Qld),
+        "Adelaide Oval" | "Football Park" | "Norwood Oval" | "Barossa Park" | "Adelaide Hills" => {
+            Some(State::Sa)
+        }
+        "Perth Stadium" | "Subiaco" | "Optus Stadium" | "Hands Oval" => Some(State::Wa),
+        "York Park" | "Bellerive Oval" | "UTAS Stadium" | "Blundstone Arena" => Some(State::Tas),
+        "Manuka Oval" => Some(State::Act),
+        "Marrara Oval" | "Traeger Park" | "TIO Stadium" => Some(State::Nt),
+        _ => None,
+    }
+}
+
+/// Home state of a club, keyed on the names Squiggle uses
+pub fn team_state(team: &str) -> Option<State> {
+    match team {
+        "Carlton" | "Collingwood" | "Essendon" | "Geelong" | "Hawthorn" | "Melbourne"
+        | "North Melbourne" | "Richmond" | "St Kilda" | "Western Bulldogs" => Some(State::Vic),
+        "Sydney" | "Greater Western Sydney" => Some(State::Nsw),
+        "Brisbane Lions" | "Gold Coast" => Some(State::Qld),
+        "Adelaide" | "Port Adelaide" => Some(State::Sa),
+        "West Coast" | "Fremantle" => Some(State::Wa),
+        _ => None,
+    }
+}